@@ -0,0 +1,190 @@
+// Campaña: niveles de los Backrooms que se recorren en orden. Cada salida lleva
+// al siguiente nivel y la salida del último termina la partida con victoria.
+// Para agregar un nivel basta con una entrada nueva en CAMPAIGN.
+
+use crate::enemy_archetypes::{EnemyArchetype, HOWLER, LURKER, SHADOW, STALKER};
+use crate::map_generators::GeneratorKind;
+
+/// Parámetros de generación del mapa de un nivel
+pub struct GeneratorParams {
+    /// Tamaño del mapa; en el modo infinito no se usa (cada chunk mide CHUNK_SIZE)
+    pub width: usize,
+    pub height: usize,
+    /// Laberinto: celdas de mapa por cada pasillo extra; menos celdas = más abierto
+    pub cells_per_extra_corridor: usize,
+    /// Laberinto: rango (min..max) de áreas abiertas por cada 40x30 celdas de mapa
+    pub open_areas: (usize, usize),
+    /// Probabilidad de que una pared junto a un pasillo tenga sangre
+    pub bloody_wall_chance: f64,
+    /// Probabilidad de que una celda vacía tenga una batería para la linterna
+    pub battery_chance: f64,
+    /// Lado en celdas de los bloques en los que se cuelga una luz del techo
+    pub light_spacing: usize,
+    /// Probabilidad de que una luz esté quemada, o de que parpadee si no lo está
+    pub broken_light_chance: f64,
+    pub flickering_light_chance: f64,
+}
+
+/// Colores del nivel: la luz multiplica cada pixel del mundo (1.0 = sin cambio)
+pub struct Palette {
+    pub light: [f32; 3],
+    /// Colores de respaldo si falta la textura de techo o suelo
+    pub ceiling: [u8; 3],
+    pub floor: [u8; 3],
+}
+
+/// Reglas de los enemigos en el nivel
+pub struct EnemyParams {
+    /// Segundos antes de que aparezcan; None = nivel sin enemigos
+    pub delay: Option<f32>,
+    /// Enemigos que aparecen, uno por entrada
+    pub roster: &'static [&'static EnemyArchetype],
+    /// Multiplica la velocidad de todos los enemigos del nivel
+    pub speed_scale: f32,
+}
+
+pub struct LevelDef {
+    pub name: &'static str,
+    /// Generadores posibles; la semilla elige uno en cada partida
+    pub generators: &'static [GeneratorKind],
+    pub generator: GeneratorParams,
+    pub palette: Palette,
+    /// Texturas propias del nivel: (nombre de la textura base, textura que la reemplaza).
+    /// Las bases son las de la tabla de tiles más "floor" y "ceiling".
+    pub textures: &'static [(&'static str, &'static str)],
+    pub enemy: EnemyParams,
+    /// Sonido en bucle de fondo; si no está cargado se usa "gameplay_sound"
+    pub ambient_sound: &'static str,
+}
+
+impl LevelDef {
+    /// Textura que usa este nivel en lugar de la textura base `name`
+    pub fn texture<'a>(&self, name: &'a str) -> &'a str {
+        self.textures
+            .iter()
+            .find(|(base, _)| *base == name)
+            .map_or(name, |(_, replacement)| replacement)
+    }
+}
+
+pub static CAMPAIGN: [LevelDef; 3] = [
+    LevelDef {
+        name: "Nivel 0: Pasillos amarillos",
+        generators: &[GeneratorKind::Maze, GeneratorKind::Office, GeneratorKind::Rooms],
+        generator: GeneratorParams {
+            width: 40,
+            height: 30,
+            cells_per_extra_corridor: 24,
+            open_areas: (3, 6),
+            bloody_wall_chance: 0.18,
+            battery_chance: 0.008,
+            light_spacing: 4,
+            broken_light_chance: 0.08,
+            flickering_light_chance: 0.15,
+        },
+        palette: Palette {
+            light: [1.0, 1.0, 1.0],
+            ceiling: [30, 25, 15],
+            floor: [50, 40, 20],
+        },
+        textures: &[],
+        enemy: EnemyParams {
+            delay: Some(10.0),
+            roster: &[&SHADOW],
+            speed_scale: 1.0,
+        },
+        ambient_sound: "gameplay_sound",
+    },
+    LevelDef {
+        name: "Nivel 1: Estacionamiento",
+        generators: &[GeneratorKind::Office, GeneratorKind::Rooms],
+        generator: GeneratorParams {
+            width: 48,
+            height: 36,
+            cells_per_extra_corridor: 10,
+            open_areas: (5, 8),
+            bloody_wall_chance: 0.08,
+            battery_chance: 0.006,
+            light_spacing: 5,
+            broken_light_chance: 0.25,
+            flickering_light_chance: 0.2,
+        },
+        palette: Palette {
+            light: [0.75, 0.8, 0.9],
+            ceiling: [35, 35, 38],
+            floor: [25, 25, 28],
+        },
+        textures: &[
+            ("wall", "garage_wall"),
+            ("floor", "garage_floor"),
+            ("ceiling", "garage_ceiling"),
+        ],
+        enemy: EnemyParams {
+            delay: Some(8.0),
+            roster: &[&SHADOW, &STALKER],
+            speed_scale: 1.07,
+        },
+        ambient_sound: "ambient_garage",
+    },
+    LevelDef {
+        name: "Nivel 2: Cuarto de tuberías",
+        generators: &[GeneratorKind::Maze, GeneratorKind::Caves],
+        generator: GeneratorParams {
+            width: 56,
+            height: 40,
+            cells_per_extra_corridor: 40,
+            open_areas: (1, 3),
+            bloody_wall_chance: 0.25,
+            battery_chance: 0.005,
+            light_spacing: 6,
+            broken_light_chance: 0.35,
+            flickering_light_chance: 0.3,
+        },
+        palette: Palette {
+            light: [1.0, 0.7, 0.55],
+            ceiling: [30, 20, 15],
+            floor: [40, 30, 25],
+        },
+        textures: &[
+            ("wall", "pipes_wall"),
+            ("floor", "pipes_floor"),
+            ("ceiling", "pipes_ceiling"),
+        ],
+        enemy: EnemyParams {
+            delay: Some(6.0),
+            roster: &[&SHADOW, &HOWLER, &LURKER],
+            speed_scale: 1.14,
+        },
+        ambient_sound: "ambient_pipes",
+    },
+];
+
+// Modo infinito: se juega fuera de la campaña, con el mundo generado por chunks
+pub static ENDLESS: LevelDef = LevelDef {
+    name: "Nivel infinito",
+    // La oficina está repetida para que sea el trazado más común
+    generators: &[GeneratorKind::Office, GeneratorKind::Office, GeneratorKind::Rooms, GeneratorKind::Maze],
+    generator: GeneratorParams {
+        width: 0,
+        height: 0,
+        cells_per_extra_corridor: 12,
+        open_areas: (1, 2),
+        bloody_wall_chance: 0.1,
+        battery_chance: 0.008,
+        light_spacing: 4,
+        broken_light_chance: 0.12,
+        flickering_light_chance: 0.2,
+    },
+    palette: Palette {
+        light: [1.0, 0.95, 0.8],
+        ceiling: [30, 25, 15],
+        floor: [50, 40, 20],
+    },
+    textures: &[],
+    enemy: EnemyParams {
+        delay: Some(20.0),
+        roster: &[&SHADOW, &HOWLER, &STALKER],
+        speed_scale: 1.0,
+    },
+    ambient_sound: "gameplay_sound",
+};
@@ -0,0 +1,324 @@
+// Mundo infinito dividido en chunks de CHUNK_SIZE x CHUNK_SIZE celdas. Cada chunk se
+// genera solo a partir de (semilla, chunk_x, chunk_y), así que descartarlo y volver a
+// generarlo da exactamente lo mismo.
+//
+// Costuras: la columna oeste y la fila norte de cada chunk son su pared de borde, con
+// puertas en posiciones que también salen de la semilla. El borde este y sur de un
+// chunk es el borde oeste/norte del vecino, y ambos calculan las mismas puertas, así
+// que los pasillos siempre continúan al otro lado.
+
+use std::collections::{HashMap, HashSet};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use crate::campaign::LevelDef;
+use crate::lighting::{CeilingLight, LightMap};
+use crate::map_generators::{add_batteries, add_bloody_walls, place_lights, GeneratorKind};
+use crate::map_validation::connect_regions;
+use crate::tile::Tile;
+use crate::world_map::{TileMap, WorldMap};
+
+pub const CHUNK_SIZE: i32 = 16;
+// Chunks alrededor del jugador que se mantienen cargados, y distancia a partir de la cual se descartan
+const LOAD_RADIUS: i32 = 2;
+const UNLOAD_RADIUS: i32 = 3;
+// Puertas por cada borde de chunk
+const MIN_DOORS: usize = 1;
+const MAX_DOORS: usize = 3;
+
+// Bordes propios de un chunk, usados para derivar la semilla de sus puertas
+#[derive(Clone, Copy)]
+enum Border {
+    West = 1,
+    North = 2,
+}
+
+pub struct ChunkedWorld {
+    seed: u64,
+    level: &'static LevelDef,
+    chunks: HashMap<(i32, i32), WorldMap>,
+    // Luces del techo de cada chunk cargado, en coordenadas del mundo
+    lights: HashMap<(i32, i32), Vec<CeilingLight>>,
+    // La salida no es parte de ningún chunk: aparece según el tiempo sobrevivido
+    exit: Option<(i32, i32)>,
+    // Celdas cuyo objeto ya se recogió; siguen vacías si su chunk se vuelve a generar
+    taken: HashSet<(i32, i32)>,
+}
+
+impl ChunkedWorld {
+    pub fn new(seed: u64, level: &'static LevelDef) -> Self {
+        Self {
+            seed,
+            level,
+            chunks: HashMap::new(),
+            lights: HashMap::new(),
+            exit: None,
+            taken: HashSet::new(),
+        }
+    }
+
+    /// Carga los chunks cercanos al punto (x, y) y descarta los lejanos. Devuelve
+    /// verdadero si cargó alguno nuevo.
+    pub fn stream_around(&mut self, x: f32, y: f32) -> bool {
+        let (center_x, center_y) = chunk_of(x.floor() as i32, y.floor() as i32);
+
+        let near = |&(chunk_x, chunk_y): &(i32, i32)| {
+            (chunk_x - center_x).abs() <= UNLOAD_RADIUS && (chunk_y - center_y).abs() <= UNLOAD_RADIUS
+        };
+        self.chunks.retain(|chunk, _| near(chunk));
+        self.lights.retain(|chunk, _| near(chunk));
+
+        let mut loaded = false;
+        for chunk_y in center_y - LOAD_RADIUS..=center_y + LOAD_RADIUS {
+            for chunk_x in center_x - LOAD_RADIUS..=center_x + LOAD_RADIUS {
+                loaded |= self.load_chunk(chunk_x, chunk_y);
+            }
+        }
+        loaded
+    }
+
+    /// Mapa de luz de los chunks que rodean al punto (x, y) hasta LOAD_RADIUS, con las
+    /// luces de todos los chunks cargados
+    pub fn light_map(&self, x: f32, y: f32) -> LightMap {
+        let (center_x, center_y) = chunk_of(x.floor() as i32, y.floor() as i32);
+        let origin = ((center_x - LOAD_RADIUS) * CHUNK_SIZE, (center_y - LOAD_RADIUS) * CHUNK_SIZE);
+        let size = ((LOAD_RADIUS * 2 + 1) * CHUNK_SIZE) as usize;
+        let lights = self.lights.values().flatten().copied().collect();
+        LightMap::build(self, origin, size, size, lights)
+    }
+
+    pub fn exit(&self) -> Option<(i32, i32)> {
+        self.exit
+    }
+
+    /// Coloca (o mueve) la salida en la celda libre más cercana al punto dado.
+    /// El chunk de destino se genera aunque esté lejos de la zona cargada.
+    pub fn place_exit_near(&mut self, x: f32, y: f32) -> (i32, i32) {
+        let (target_x, target_y) = (x.floor() as i32, y.floor() as i32);
+        let chunk = chunk_of(target_x, target_y);
+        self.load_chunk(chunk.0, chunk.1);
+
+        // Todo chunk tiene celdas libres (al menos las que dan a sus puertas)
+        let origin = (chunk.0 * CHUNK_SIZE, chunk.1 * CHUNK_SIZE);
+        let exit = (0..CHUNK_SIZE)
+            .flat_map(|local_y| (0..CHUNK_SIZE).map(move |local_x| (origin.0 + local_x, origin.1 + local_y)))
+            .filter(|&(cell_x, cell_y)| self.tile(cell_x, cell_y) == Some(Tile::Empty))
+            .min_by_key(|&(cell_x, cell_y)| (cell_x - target_x).pow(2) + (cell_y - target_y).pow(2))
+            .unwrap_or(origin);
+
+        self.exit = Some(exit);
+        exit
+    }
+
+    /// Vacía la celda (x, y) para siempre, aunque su chunk se descarte y se regenere
+    pub fn take_pickup(&mut self, x: i32, y: i32) {
+        self.taken.insert((x, y));
+        if let Some(chunk) = self.chunks.get_mut(&chunk_of(x, y)) {
+            chunk.set(x.rem_euclid(CHUNK_SIZE) as usize, y.rem_euclid(CHUNK_SIZE) as usize, Tile::Empty);
+        }
+    }
+
+    // Genera el chunk si no está cargado; devuelve verdadero si lo generó
+    fn load_chunk(&mut self, chunk_x: i32, chunk_y: i32) -> bool {
+        if self.chunks.contains_key(&(chunk_x, chunk_y)) {
+            return false;
+        }
+
+        let mut chunk = generate_chunk(self.seed, self.level, chunk_x, chunk_y);
+        for &(x, y) in &self.taken {
+            if chunk_of(x, y) == (chunk_x, chunk_y) {
+                chunk.set(x.rem_euclid(CHUNK_SIZE) as usize, y.rem_euclid(CHUNK_SIZE) as usize, Tile::Empty);
+            }
+        }
+        let lights = chunk_lights(self.seed, self.level, chunk_x, chunk_y, &chunk);
+        self.chunks.insert((chunk_x, chunk_y), chunk);
+        self.lights.insert((chunk_x, chunk_y), lights);
+        true
+    }
+}
+
+impl TileMap for ChunkedWorld {
+    fn tile(&self, x: i32, y: i32) -> Option<Tile> {
+        if self.exit == Some((x, y)) {
+            return Some(Tile::Exit);
+        }
+
+        let (chunk_x, chunk_y) = chunk_of(x, y);
+        let chunk = self.chunks.get(&(chunk_x, chunk_y))?;
+        chunk.get(x.rem_euclid(CHUNK_SIZE) as usize, y.rem_euclid(CHUNK_SIZE) as usize)
+    }
+}
+
+// Chunk que contiene la celda (x, y)
+fn chunk_of(x: i32, y: i32) -> (i32, i32) {
+    (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE))
+}
+
+// Mezcla de bits de splitmix64: semillas cercanas dan resultados sin relación
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn chunk_rng(seed: u64, chunk_x: i32, chunk_y: i32, salt: u64) -> StdRng {
+    let position = (chunk_x as u32 as u64) | ((chunk_y as u32 as u64) << 32);
+    StdRng::seed_from_u64(mix(seed ^ mix(position) ^ mix(salt)))
+}
+
+// Posiciones de las puertas en un borde propio del chunk, lejos de las esquinas
+fn doors(seed: u64, chunk_x: i32, chunk_y: i32, border: Border) -> Vec<usize> {
+    let mut rng = chunk_rng(seed, chunk_x, chunk_y, border as u64);
+    let mut positions: Vec<usize> = (2..CHUNK_SIZE as usize - 1).collect();
+    positions.shuffle(&mut rng);
+    positions.truncate(rng.gen_range(MIN_DOORS..=MAX_DOORS));
+    positions
+}
+
+/// Genera el chunk (chunk_x, chunk_y). El resultado depende solo de los argumentos.
+pub fn generate_chunk(seed: u64, level: &LevelDef, chunk_x: i32, chunk_y: i32) -> WorldMap {
+    let size = CHUNK_SIZE as usize;
+    let mut rng = chunk_rng(seed, chunk_x, chunk_y, 0);
+
+    // El interior del chunk (celdas 1..size) se genera como un mapa de size+1 con
+    // borde de pared en ambos lados; el borde este/sur de ese mapa no se copia
+    let mut interior = WorldMap::new(size + 1, size + 1, Tile::Wall);
+    let generator = level.generators.choose(&mut rng).copied().unwrap_or(GeneratorKind::Office);
+    generator.generator().carve(&mut interior, &level.generator, &mut rng);
+    add_bloody_walls(&mut interior, level.generator.bloody_wall_chance, &mut rng);
+    add_batteries(&mut interior, level.generator.battery_chance, &mut rng);
+
+    // Abrir la celda interior junto a cada puerta, propia o del vecino
+    let west = doors(seed, chunk_x, chunk_y, Border::West);
+    let north = doors(seed, chunk_x, chunk_y, Border::North);
+    let east = doors(seed, chunk_x + 1, chunk_y, Border::West);
+    let south = doors(seed, chunk_x, chunk_y + 1, Border::North);
+
+    let mut entrances = Vec::new();
+    entrances.extend(west.iter().map(|&door| (1, door)));
+    entrances.extend(north.iter().map(|&door| (door, 1)));
+    entrances.extend(east.iter().map(|&door| (size - 1, door)));
+    entrances.extend(south.iter().map(|&door| (door, size - 1)));
+
+    // El chunk de origen tiene un área libre en el centro para el inicio del jugador
+    if (chunk_x, chunk_y) == (0, 0) {
+        let center = size / 2;
+        for y in center - 1..=center + 1 {
+            for x in center - 1..=center + 1 {
+                entrances.push((x, y));
+            }
+        }
+    }
+
+    for &(x, y) in &entrances {
+        interior.set(x, y, Tile::Empty);
+    }
+
+    // Unir todas las zonas del interior, así cada puerta llega a todas las demás
+    connect_regions(&mut interior, entrances[0]);
+
+    let mut chunk = WorldMap::new(size, size, Tile::Wall);
+    for y in 1..size {
+        for x in 1..size {
+            if let Some(tile) = interior.get(x, y) {
+                chunk.set(x, y, tile);
+            }
+        }
+    }
+    for &door in &west {
+        chunk.set(0, door, Tile::Empty);
+    }
+    for &door in &north {
+        chunk.set(door, 0, Tile::Empty);
+    }
+
+    chunk
+}
+
+// Luces del techo del chunk, en coordenadas del mundo. Dependen solo de los argumentos.
+fn chunk_lights(seed: u64, level: &LevelDef, chunk_x: i32, chunk_y: i32, chunk: &WorldMap) -> Vec<CeilingLight> {
+    let mut rng = chunk_rng(seed, chunk_x, chunk_y, 3);
+    let mut lights = place_lights(chunk, &level.generator, &mut rng);
+    for light in &mut lights {
+        light.x += chunk_x * CHUNK_SIZE;
+        light.y += chunk_y * CHUNK_SIZE;
+    }
+    lights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaign::ENDLESS;
+    use crate::map_validation::flood_fill;
+
+    // Copia de un bloque de chunks a un mapa fijo, para validarlo con las mismas herramientas
+    fn block(seed: u64, chunks: i32) -> WorldMap {
+        let mut world = ChunkedWorld::new(seed, &ENDLESS);
+        for chunk_y in 0..chunks {
+            for chunk_x in 0..chunks {
+                world.load_chunk(chunk_x, chunk_y);
+            }
+        }
+
+        let size = (chunks * CHUNK_SIZE) as usize;
+        let mut map = WorldMap::new(size + 1, size + 1, Tile::Wall);
+        for y in 0..size {
+            for x in 0..size {
+                // El borde oeste/norte del bloque es de pared, como el borde de un mapa fijo
+                if x > 0 && y > 0 {
+                    map.set(x, y, world.tile(x as i32, y as i32).unwrap());
+                }
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn chunks_are_deterministic() {
+        for (chunk_x, chunk_y) in [(0, 0), (3, -2), (-7, 11)] {
+            assert_eq!(
+                generate_chunk(42, &ENDLESS, chunk_x, chunk_y),
+                generate_chunk(42, &ENDLESS, chunk_x, chunk_y)
+            );
+        }
+        assert_ne!(generate_chunk(42, &ENDLESS, 1, 0), generate_chunk(43, &ENDLESS, 1, 0));
+    }
+
+    #[test]
+    fn chunks_connect_across_their_borders() {
+        for seed in 0..40 {
+            let map = block(seed, 4);
+            let start = (CHUNK_SIZE as usize / 2, CHUNK_SIZE as usize / 2);
+            let distances = flood_fill(&map, start);
+
+            for y in 0..map.height() {
+                for x in 0..map.width() {
+                    let open = map.get(x, y).is_some_and(|tile| !tile.is_solid_for_player());
+                    assert!(
+                        !open || distances[y * map.width() + x].is_some(),
+                        "semilla {}: celda ({}, {}) aislada",
+                        seed, x, y
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn far_chunks_are_evicted() {
+        let mut world = ChunkedWorld::new(7, &ENDLESS);
+        world.stream_around(8.0, 8.0);
+        assert_eq!(world.chunks.len(), 25);
+
+        // Caminar lejos descarta lo que quedó atrás, sin acumular chunks
+        for step in 0..50 {
+            world.stream_around(8.0 + step as f32 * 10.0, 8.0);
+            assert!(world.chunks.len() <= ((UNLOAD_RADIUS * 2 + 1) * (LOAD_RADIUS * 2 + 1)) as usize);
+        }
+        assert_eq!(world.tile(0, 0), None);
+
+        // Volver genera de nuevo exactamente el mismo chunk
+        world.stream_around(8.0, 8.0);
+        assert_eq!(world.chunks[&(0, 0)], generate_chunk(7, &ENDLESS, 0, 0));
+    }
+}
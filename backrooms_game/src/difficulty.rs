@@ -0,0 +1,139 @@
+// Niveles de dificultad que se eligen en el menú. Cada uno ajusta a los enemigos,
+// el tamaño de los mapas, los sustos y lo que aguanta el jugador corriendo.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+pub struct DifficultySettings {
+    /// Multiplica la velocidad de todos los enemigos
+    pub enemy_speed: f32,
+    /// Enemigos de más (o de menos, si es negativo) respecto a los del nivel; siempre queda al menos uno
+    pub extra_enemies: i32,
+    /// Multiplica los segundos que tardan en aparecer los enemigos
+    pub enemy_delay: f32,
+    /// Multiplica el ancho y alto de los mapas generados
+    pub map_size: f32,
+    pub minimap: bool,
+    /// Multiplica la frecuencia de los sustos del director
+    pub scare_rate: f32,
+    /// Segundos que el jugador puede correr con la stamina llena
+    pub stamina: f32,
+}
+
+static EASY: DifficultySettings = DifficultySettings {
+    enemy_speed: 0.85,
+    extra_enemies: -1,
+    enemy_delay: 1.5,
+    map_size: 0.8,
+    minimap: true,
+    scare_rate: 0.5,
+    stamina: 8.0,
+};
+
+static NORMAL: DifficultySettings = DifficultySettings {
+    enemy_speed: 1.0,
+    extra_enemies: 0,
+    enemy_delay: 1.0,
+    map_size: 1.0,
+    minimap: true,
+    scare_rate: 1.0,
+    stamina: 5.0,
+};
+
+static HARD: DifficultySettings = DifficultySettings {
+    enemy_speed: 1.1,
+    extra_enemies: 1,
+    enemy_delay: 0.7,
+    map_size: 1.2,
+    minimap: true,
+    scare_rate: 1.5,
+    stamina: 4.0,
+};
+
+static NIGHTMARE: DifficultySettings = DifficultySettings {
+    enemy_speed: 1.2,
+    extra_enemies: 2,
+    enemy_delay: 0.4,
+    map_size: 1.4,
+    minimap: false,
+    scare_rate: 2.5,
+    stamina: 3.0,
+};
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Nightmare,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    pub fn settings(self) -> &'static DifficultySettings {
+        match self {
+            Difficulty::Easy => &EASY,
+            Difficulty::Normal => &NORMAL,
+            Difficulty::Hard => &HARD,
+            Difficulty::Nightmare => &NIGHTMARE,
+        }
+    }
+
+    /// Dificultad siguiente (step = 1) o anterior (step = -1) en la lista, sin dar la vuelta
+    pub fn step(self, step: i32) -> Difficulty {
+        let index = Difficulty::ALL.iter().position(|&difficulty| difficulty == self).unwrap_or(1) as i32;
+        Difficulty::ALL[(index + step).clamp(0, Difficulty::ALL.len() as i32 - 1) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::GameState;
+
+    #[test]
+    fn stepping_stops_at_both_ends() {
+        assert_eq!(Difficulty::Normal.step(1), Difficulty::Hard);
+        assert_eq!(Difficulty::Normal.step(-1), Difficulty::Easy);
+        assert_eq!(Difficulty::Easy.step(-1), Difficulty::Easy);
+        assert_eq!(Difficulty::Nightmare.step(1), Difficulty::Nightmare);
+        assert_eq!(Difficulty::Easy.step(10), Difficulty::Nightmare);
+    }
+
+    #[test]
+    fn roster_size_follows_difficulty_and_cycles_archetypes() {
+        let mut game_state = GameState::new(Some(0));
+        game_state.level_index = 2;
+        let roster: Vec<&str> = game_state.level_def().enemy.roster.iter().map(|archetype| archetype.name).collect();
+        assert_eq!(roster.len(), 3);
+
+        for difficulty in Difficulty::ALL {
+            game_state.difficulty = difficulty;
+            let names: Vec<&str> = game_state.enemy_roster().iter().map(|archetype| archetype.name).collect();
+            let expected = (roster.len() as i32 + difficulty.settings().extra_enemies).max(1) as usize;
+            assert_eq!(names.len(), expected, "{}", difficulty.name());
+            // Los de más repiten el plantel desde el principio
+            for (index, name) in names.iter().enumerate() {
+                assert_eq!(*name, roster[index % roster.len()]);
+            }
+        }
+
+        // Aunque la dificultad quite enemigos, siempre queda al menos uno
+        game_state.level_index = 0;
+        game_state.difficulty = Difficulty::Easy;
+        assert_eq!(game_state.level_def().enemy.roster.len(), 1);
+        assert_eq!(game_state.enemy_roster().len(), 1);
+    }
+}
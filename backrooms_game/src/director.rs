@@ -0,0 +1,217 @@
+// Director: lleva la cuenta de la tensión del jugador y decide cuándo soltar a los
+// enemigos y cuándo meter un susto, alternando con momentos de calma.
+//
+// La tensión sube con los enemigos cerca, con las persecuciones y con cada susto, y
+// baja sola con el tiempo. El ritmo sigue tres fases:
+//   BuildUp: la tensión crece; si el jugador está tranquilo puede llegar un susto
+//   Peak:    la tensión pasó PEAK_STRESS; no se agrega nada más
+//   Relax:   después del pico, RELAX_TIME segundos sin sustos ni enemigos nuevos
+// Todo se calcula por segundo, así que el resultado no depende de los FPS.
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+// Tensión por segundo con un enemigo pegado al jugador (baja con la distancia hasta
+// PROXIMITY_RANGE), tensión por segundo mientras lo persiguen, y tensión de cada susto
+const PROXIMITY_RANGE: f32 = 10.0;
+const PROXIMITY_STRESS: f32 = 0.25;
+const CHASE_STRESS: f32 = 0.15;
+const SCARE_STRESS: f32 = 0.35;
+// Fracción de tensión que se pierde por segundo
+const STRESS_DECAY: f32 = 0.08;
+// Umbrales de las fases y duración de la calma
+const PEAK_STRESS: f32 = 0.75;
+const RELAX_STRESS: f32 = 0.5;
+const RELAX_TIME: f32 = 20.0;
+// Sustos: no antes de FIRST_SCARE_TIME, con al menos MIN_QUIET_TIME desde el último evento,
+// solo con poca tensión, y en promedio uno cada 1 / SCARE_RATE segundos cuando se cumple todo
+const FIRST_SCARE_TIME: f32 = 20.0;
+const MIN_QUIET_TIME: f32 = 45.0;
+const SCARE_MAX_STRESS: f32 = 0.4;
+const SCARE_RATE: f32 = 1.0 / 30.0;
+// Si la calma se alarga, los enemigos aparecen igual a lo sumo este tiempo después de su demora
+const MAX_SPAWN_WAIT: f32 = 15.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    BuildUp,
+    Peak,
+    Relax,
+}
+
+/// Lo que el director pide que pase
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DirectorEvent {
+    SpawnEnemies,
+    Scare,
+}
+
+/// Lo que el director necesita saber de la partida en cada cuadro
+pub struct Tension {
+    /// Distancia del enemigo activo más cercano
+    pub nearest_enemy: Option<f32>,
+    /// Algún enemigo está persiguiendo al jugador
+    pub chased: bool,
+}
+
+pub struct Director {
+    stress: f32,
+    phase: Phase,
+    phase_timer: f32,
+    elapsed: f32,
+    since_last_event: f32,
+    // Demora mínima de los enemigos del nivel (None = nivel sin enemigos), y si ya aparecieron
+    enemy_delay: Option<f32>,
+    enemies_spawned: bool,
+    // Multiplica SCARE_RATE según la dificultad
+    scare_rate: f32,
+    rng: StdRng,
+}
+
+impl Director {
+    pub fn new(enemy_delay: Option<f32>, scare_rate: f32, seed: u64) -> Self {
+        Self {
+            stress: 0.0,
+            phase: Phase::BuildUp,
+            phase_timer: 0.0,
+            elapsed: 0.0,
+            since_last_event: 0.0,
+            enemy_delay,
+            enemies_spawned: false,
+            scare_rate,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Tensión actual, de 0 (tranquilo) a 1
+    pub fn stress(&self) -> f32 {
+        self.stress
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Algo asustó al jugador por fuera del director (screamer de salida, un enemigo que lo ve)
+    pub fn on_scare(&mut self) {
+        self.stress = (self.stress + SCARE_STRESS).min(1.0);
+        self.since_last_event = 0.0;
+    }
+
+    pub fn update(&mut self, dt: f32, tension: &Tension) -> Vec<DirectorEvent> {
+        self.elapsed += dt;
+        self.since_last_event += dt;
+        self.phase_timer += dt;
+
+        let mut stress_rate = 0.0;
+        if let Some(distance) = tension.nearest_enemy {
+            stress_rate += (1.0 - distance / PROXIMITY_RANGE).max(0.0) * PROXIMITY_STRESS;
+        }
+        if tension.chased {
+            stress_rate += CHASE_STRESS;
+        }
+        self.stress = ((self.stress + stress_rate * dt) * (-STRESS_DECAY * dt).exp()).clamp(0.0, 1.0);
+
+        match self.phase {
+            Phase::BuildUp if self.stress >= PEAK_STRESS => self.set_phase(Phase::Peak),
+            Phase::Peak if self.stress < RELAX_STRESS => self.set_phase(Phase::Relax),
+            Phase::Relax if self.phase_timer >= RELAX_TIME => self.set_phase(Phase::BuildUp),
+            _ => {}
+        }
+
+        let mut events = Vec::new();
+
+        if let Some(delay) = self.enemy_delay {
+            let waited_too_long = self.elapsed >= delay + MAX_SPAWN_WAIT;
+            if !self.enemies_spawned && self.elapsed >= delay && (self.phase != Phase::Relax || waited_too_long) {
+                self.enemies_spawned = true;
+                self.since_last_event = 0.0;
+                events.push(DirectorEvent::SpawnEnemies);
+            }
+        }
+
+        let scare_allowed = self.phase == Phase::BuildUp
+            && self.elapsed >= FIRST_SCARE_TIME
+            && self.since_last_event >= MIN_QUIET_TIME
+            && self.stress < SCARE_MAX_STRESS;
+        // Probabilidad de al menos un susto en dt segundos con una tasa constante
+        if scare_allowed && self.rng.gen::<f32>() < 1.0 - (-SCARE_RATE * self.scare_rate * dt).exp() {
+            self.on_scare();
+            events.push(DirectorEvent::Scare);
+        }
+
+        events
+    }
+
+    fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.phase_timer = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALM: Tension = Tension { nearest_enemy: None, chased: false };
+
+    // Simula `seconds` segundos con pasos de `dt` y devuelve todos los eventos
+    fn run(director: &mut Director, seconds: f32, dt: f32, tension: &Tension) -> Vec<DirectorEvent> {
+        (0..(seconds / dt).round() as usize).flat_map(|_| director.update(dt, tension)).collect()
+    }
+
+    #[test]
+    fn spawns_enemies_once_after_their_delay() {
+        let mut director = Director::new(Some(10.0), 1.0, 1);
+        assert!(run(&mut director, 9.9, 0.1, &CALM).is_empty());
+        assert_eq!(run(&mut director, 0.2, 0.1, &CALM), vec![DirectorEvent::SpawnEnemies]);
+        assert!(!run(&mut director, 5.0, 0.1, &CALM).contains(&DirectorEvent::SpawnEnemies));
+
+        let mut without_enemies = Director::new(None, 1.0, 1);
+        assert!(!run(&mut without_enemies, 60.0, 0.1, &CALM).contains(&DirectorEvent::SpawnEnemies));
+    }
+
+    #[test]
+    fn stress_does_not_depend_on_frame_rate() {
+        let near = Tension { nearest_enemy: Some(3.0), chased: true };
+        let mut slow = Director::new(None, 1.0, 1);
+        let mut fast = Director::new(None, 1.0, 1);
+        run(&mut slow, 4.0, 1.0 / 20.0, &near);
+        run(&mut fast, 4.0, 1.0 / 240.0, &near);
+
+        assert!(slow.stress() > 0.3);
+        assert!((slow.stress() - fast.stress()).abs() < 0.01);
+    }
+
+    #[test]
+    fn peaks_are_followed_by_a_calm_period() {
+        let mut director = Director::new(Some(1.0), 1.0, 1);
+        run(&mut director, 60.0, 0.1, &CALM);
+        director.on_scare();
+        director.on_scare();
+        director.on_scare();
+        run(&mut director, 0.1, 0.1, &CALM);
+        assert_eq!(director.phase(), Phase::Peak);
+
+        // La tensión baja sola hasta la calma, y durante la calma no hay sustos
+        let mut seconds = 0.0;
+        while director.phase() == Phase::Peak {
+            run(&mut director, 0.1, 0.1, &CALM);
+            seconds += 0.1;
+            assert!(seconds < 30.0);
+        }
+        assert_eq!(director.phase(), Phase::Relax);
+        assert!(run(&mut director, RELAX_TIME - 0.5, 0.1, &CALM).is_empty());
+    }
+
+    #[test]
+    fn calm_players_get_scared_eventually() {
+        let scares = |dt: f32| {
+            let mut director = Director::new(None, 1.0, 7);
+            run(&mut director, 1200.0, dt, &CALM).len()
+        };
+        // Unos 20 minutos tranquilos dan varios sustos, parecidos a cualquier tasa de cuadros
+        let (slow, fast) = (scares(1.0 / 20.0), scares(1.0 / 120.0));
+        assert!(slow >= 5 && fast >= 5, "{} {}", slow, fast);
+        assert!(slow.abs_diff(fast) <= 6, "{} {}", slow, fast);
+    }
+}
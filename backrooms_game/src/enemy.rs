@@ -1,8 +1,34 @@
 use macroquad::prelude::*;
+use std::collections::HashMap;
+use ::rand::{Rng, seq::SliceRandom};
+use crate::enemy_ai::{AiEvent, AiState, EnemyAi};
+use crate::enemy_archetypes::{Behavior, EnemyArchetype, ARCHETYPES};
+use crate::noise::{Noise, NoiseField};
+use crate::pathfinding::{find_path, is_clear, smooth_path};
 use crate::player::Player;
-// use rand::Rng;
+use crate::raycaster::has_line_of_sight;
+use crate::world_map::TileMap;
+use crate::sprites::Sprite;
+use crate::textures::generate_enemy_texture;
+
+// Distancia al jugador (en celdas) a la que puede aparecer el enemigo
+const MIN_SPAWN_DISTANCE: f32 = 8.0;
+const MAX_SPAWN_DISTANCE: f32 = 20.0;
+// Si no hay lugar lejos del jugador, cada cuánto se vuelve a intentar la aparición
+const SPAWN_RETRY_INTERVAL: f32 = 1.0;
+// Cada cuánto se recalcula el camino hacia el objetivo
+const REPLAN_INTERVAL: f32 = 0.5;
+// Distancia a la que un punto del camino se da por alcanzado
+const WAYPOINT_REACHED: f32 = 0.15;
+// Visión: medio ángulo del cono hacia donde camina, y distancia a la que nota al jugador sin importar hacia dónde mire
+const SIGHT_HALF_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+const SENSE_DISTANCE: f32 = 2.0;
+// Ruta de patrulla: cantidad de puntos y distancia al lugar de aparición
+const PATROL_POINTS: usize = 4;
+const PATROL_RADIUS: (f32, f32) = (4.0, 12.0);
 
 pub struct Enemy {
+    pub archetype: &'static EnemyArchetype,
     pub x: f32,
     pub y: f32,
     pub speed: f32,
@@ -11,139 +37,326 @@ pub struct Enemy {
     pub texture: Option<Texture2D>,
     pub size: f32,
     pub active: bool, // Para controlar si el enemigo está activo
+    /// Radio del cuerpo para colisiones y para decidir por dónde cabe
+    pub radius: f32,
+    /// Dirección hacia la que camina (radianes); define su cono de visión
+    pub facing: f32,
+    ai: EnemyAi,
+    // Puntos del mundo que faltan por recorrer hasta el objetivo de la IA, el objetivo
+    // para el que se calcularon, y tiempo hasta recalcularlos
+    path: Vec<(f32, f32)>,
+    path_goal: Option<(f32, f32)>,
+    replan_timer: f32,
+    // El último A* no encontró camino y persigue en línea recta hasta el próximo recálculo
+    search_failed: bool,
+    // Tiempo hasta reintentar la aparición cuando no encontró lugar
+    spawn_timer: f32,
 }
 
 impl Enemy {
-    pub fn new() -> Self {
+    pub fn new(archetype: &'static EnemyArchetype, texture: Option<Texture2D>) -> Self {
         Self {
+            archetype,
             x: 10.0, // Posición inicial alejada del jugador
             y: 10.0,
-            speed: 2.8, // Aumentado de 1.8 a 2.8 para ser más rápido
+            speed: archetype.speed,
             alive: true,
-            catch_distance: 1.0, // Distancia para atrapar al jugador
-            texture: None,
-            size: 1.8, // Tamaño del enemigo en el mundo
+            catch_distance: archetype.catch_distance,
+            texture,
+            size: archetype.size,
             active: false, // Inicia inactivo
+            radius: archetype.radius,
+            facing: 0.0,
+            ai: EnemyAi::new(),
+            path: Vec::new(),
+            path_goal: None,
+            replan_timer: 0.0,
+            search_failed: false,
+            spawn_timer: 0.0,
         }
     }
     
-    pub async fn load_texture(&mut self) {
-        println!("Intentando cargar imagen del enemigo...");
-        
-        if std::path::Path::new("enemigo.png").exists() {
-            match load_texture("enemigo.png").await {
-                Ok(texture) => {
-                    self.texture = Some(texture);
-                    println!("✓ Imagen enemigo cargada: enemigo.png");
-                    return;
-                }
-                Err(e) => println!("✗ Error cargando enemigo.png: {}", e),
-            }
+    /// Aparece lejos del jugador y empieza a actuar. Si no hay ningún lugar a la distancia
+    /// mínima queda inactivo y devuelve false; `retry_spawn` lo vuelve a intentar.
+    pub fn activate(&mut self, player: &Player, world_map: &dyn TileMap, spawn_points: &[(usize, usize)], rng: &mut impl Rng) -> bool {
+        self.path.clear();
+        self.path_goal = None;
+        self.replan_timer = 0.0;
+        self.search_failed = false;
+        
+        // Los niveles hechos a mano pueden fijar dónde aparece el enemigo; los puntos
+        // demasiado cerca del jugador no sirven
+        let far_points: Vec<(usize, usize)> = spawn_points
+            .iter()
+            .copied()
+            .filter(|&(x, y)| {
+                let (dx, dy) = (x as f32 + 0.5 - player.x, y as f32 + 0.5 - player.y);
+                (dx * dx + dy * dy).sqrt() >= MIN_SPAWN_DISTANCE
+            })
+            .collect();
+        if let Some(&(x, y)) = far_points.choose(rng) {
+            self.x = x as f32 + 0.5;
+            self.y = y as f32 + 0.5;
+            println!("Enemigo spawneado en punto del nivel: ({:.1}, {:.1})", self.x, self.y);
+        } else if !self.find_spawn_position(player, world_map, rng) {
+            // Ninguna celda libre está lo bastante lejos: esperar y reintentar
+            self.active = false;
+            self.spawn_timer = SPAWN_RETRY_INTERVAL;
+            println!("✗ Sin lugar para el enemigo lejos del jugador, se reintenta en {:.0}s", SPAWN_RETRY_INTERVAL);
+            return false;
         }
+        self.active = true;
+        self.alive = true;
         
-        // Intentar rutas alternativas
-        let alternative_paths = vec![
-            "./enemigo.png",
-            "../enemigo.png",
-            "assets/images/enemigo.png",
-            "./assets/images/enemigo.png",
-            "assets/enemigo.png",
-        ];
-        
-        for alt_path in alternative_paths {
-            if std::path::Path::new(&alt_path).exists() {
-                println!("  → Intentando ruta alternativa: {}", alt_path);
-                match load_texture(&alt_path).await {
-                    Ok(texture) => {
-                        self.texture = Some(texture);
-                        println!("  ✓ Imagen enemigo cargada desde ruta alternativa: {}", alt_path);
-                        return;
-                    }
-                    Err(e) => println!("  ✗ Error en ruta alternativa {}: {}", alt_path, e),
-                }
+        match self.archetype.behavior {
+            // Llega atraído por el jugador: empieza yendo a ver dónde estaba
+            Behavior::Hunter => {
+                let patrol = self.patrol_route(world_map, rng);
+                self.ai.reset(patrol);
+                self.ai.hear_noise(player.x, player.y);
+            }
+            // Sin ruta: espera donde apareció, mirando hacia cualquier lado
+            Behavior::Lurker | Behavior::Stalker => {
+                self.ai.reset(Vec::new());
+                self.facing = rng.gen_range(0.0..std::f32::consts::TAU);
             }
         }
-        
-        println!("  ✗ No se pudo encontrar enemigo.png en ninguna ubicación");
-        println!("  → Se usará un enemigo generado por código");
+        true
     }
     
-    pub fn activate(&mut self, player: &Player, world_map: &[[u8; 40]; 30]) {
-        self.active = true;
-        self.alive = true;
-        // Encontrar una posición válida lejos del jugador para spawnear
-        self.find_spawn_position(player, world_map);
+    /// Reintenta la aparición de un enemigo que quedó esperando lugar
+    pub fn retry_spawn(&mut self, dt: f32, player: &Player, world_map: &dyn TileMap, spawn_points: &[(usize, usize)], rng: &mut impl Rng) {
+        if self.active {
+            return;
+        }
+        self.spawn_timer -= dt;
+        if self.spawn_timer <= 0.0 {
+            self.activate(player, world_map, spawn_points, rng);
+        }
     }
     
-    fn find_spawn_position(&mut self, player: &Player, world_map: &[[u8; 40]; 30]) {
+    // Puntos libres y alcanzables alrededor del lugar de aparición, recorridos en bucle
+    fn patrol_route(&self, world_map: &dyn TileMap, rng: &mut impl Rng) -> Vec<(f32, f32)> {
+        let start = (self.x.floor() as i32, self.y.floor() as i32);
+        let mut route = vec![(self.x, self.y)];
+        
+        for _ in 0..PATROL_POINTS * 10 {
+            if route.len() > PATROL_POINTS {
+                break;
+            }
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let distance = rng.gen_range(PATROL_RADIUS.0..PATROL_RADIUS.1);
+            let cell = ((self.x + angle.cos() * distance).floor() as i32, (self.y + angle.sin() * distance).floor() as i32);
+            if find_path(world_map, start, cell).is_some() {
+                route.push((cell.0 as f32 + 0.5, cell.1 as f32 + 0.5));
+            }
+        }
+        
+        route
+    }
+    
+    // Celda libre entre MIN_SPAWN_DISTANCE y MAX_SPAWN_DISTANCE del jugador; false si no hay ninguna
+    fn find_spawn_position(&mut self, player: &Player, world_map: &dyn TileMap, rng: &mut impl Rng) -> bool {
         let mut spawn_attempts = 0;
         let max_attempts = 100;
         
         while spawn_attempts < max_attempts {
-            // Generar posición aleatoria
-            let spawn_x = rand::gen_range(5, 35) as f32 + 0.5;
-            let spawn_y = rand::gen_range(5, 25) as f32 + 0.5;
-            
-            // Verificar que esté en un espacio libre
-            let map_x = spawn_x as usize;
-            let map_y = spawn_y as usize;
+            // Posición aleatoria alrededor del jugador; sirve igual en mapas fijos y en el mundo infinito
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let distance = rng.gen_range(MIN_SPAWN_DISTANCE..MAX_SPAWN_DISTANCE);
+            let spawn_x = (player.x + angle.cos() * distance).floor() + 0.5;
+            let spawn_y = (player.y + angle.sin() * distance).floor() + 0.5;
             
-            if map_x < 40 && map_y < 30 && 
-               (world_map[map_y][map_x] == 0 || world_map[map_y][map_x] == 3) {
-                
-                // Verificar que esté a una distancia mínima del jugador
-                let distance_to_player = ((spawn_x - player.x).powi(2) + (spawn_y - player.y).powi(2)).sqrt();
-                
-                if distance_to_player > 8.0 && distance_to_player < 20.0 {
-                    self.x = spawn_x;
-                    self.y = spawn_y;
-                    println!("Enemigo spawneado en posición: ({:.1}, {:.1})", spawn_x, spawn_y);
-                    return;
-                }
+            // Verificar que esté en un espacio libre y que al centrarlo en la celda no quede cerca
+            let distance = ((spawn_x - player.x).powi(2) + (spawn_y - player.y).powi(2)).sqrt();
+            if distance >= MIN_SPAWN_DISTANCE && Self::is_walkable(world_map, spawn_x, spawn_y) {
+                self.x = spawn_x;
+                self.y = spawn_y;
+                println!("Enemigo spawneado en posición: ({:.1}, {:.1})", spawn_x, spawn_y);
+                return true;
             }
             
             spawn_attempts += 1;
         }
         
-        // Posición de fallback si no se encuentra una buena posición
-        self.x = 35.0;
-        self.y = 25.0;
+        // Fallback: la celda libre más lejana dentro del alcance, recorriendo alrededor del jugador
+        let reach = MAX_SPAWN_DISTANCE as i32;
+        let (cell_x, cell_y) = (player.x.floor() as i32, player.y.floor() as i32);
+        let mut best = None;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let x = (cell_x + dx) as f32 + 0.5;
+                let y = (cell_y + dy) as f32 + 0.5;
+                let distance = ((x - player.x).powi(2) + (y - player.y).powi(2)).sqrt();
+                let farther = best.is_none_or(|(_, _, best_distance)| distance > best_distance);
+                if farther
+                    && (MIN_SPAWN_DISTANCE..MAX_SPAWN_DISTANCE).contains(&distance)
+                    && Self::is_walkable(world_map, x, y)
+                {
+                    best = Some((x, y, distance));
+                }
+            }
+        }
+        
+        let Some((x, y, _)) = best else {
+            return false;
+        };
+        self.x = x;
+        self.y = y;
         println!("Enemigo spawneado en posición de fallback");
+        true
     }
     
-    pub fn update(&mut self, dt: f32, player: &Player, world_map: &[[u8; 40]; 30]) {
+    /// `observed` indica si el jugador lo tiene en pantalla sin nada delante
+    pub fn update(&mut self, dt: f32, player: &Player, world_map: &dyn TileMap, observed: bool) {
         if !self.alive || !self.active {
             return;
         }
         
-        // Calcular dirección hacia el jugador
-        let dx = player.x - self.x;
-        let dy = player.y - self.y;
-        let distance = (dx * dx + dy * dy).sqrt();
+        // Mientras lo miran queda congelado por completo, hasta en sus decisiones
+        if observed && self.archetype.behavior == Behavior::Stalker {
+            return;
+        }
+        
+        let seen = self.can_see(player, world_map).then_some((player.x, player.y));
+        self.ai.update(dt, (self.x, self.y), seen);
+        
+        let Some(goal) = self.ai.goal() else {
+            self.path.clear();
+            return;
+        };
+        
+        // Recalcular el camino cada cierto tiempo (el jugador se mueve) o si la IA cambió de objetivo.
+        // Si la última búsqueda falló, solo se corrige la línea recta hasta que toque recalcular.
+        self.replan_timer -= dt;
+        let chasing_blind = self.search_failed && self.ai.state() == AiState::Chase;
+        if chasing_blind && self.replan_timer > 0.0 && !self.path.is_empty() {
+            self.path = vec![goal];
+            self.path_goal = Some(goal);
+        } else if self.replan_timer <= 0.0 || self.path.is_empty() || self.path_goal != Some(goal) {
+            self.plan_path(goal, world_map);
+            self.replan_timer = REPLAN_INTERVAL;
+        }
+        
+        // Hacer al enemigo más rápido cuando persigue de cerca al jugador
+        let distance = self.get_distance_to_player(player);
+        let speed_multiplier = match self.ai.state() {
+            AiState::Chase => self.archetype.chase_speed(distance),
+            _ => self.ai.speed_factor(),
+        };
+        let mut remaining = self.speed * speed_multiplier * dt;
         
-        if distance > 0.1 {
-            // Normalizar dirección
-            let dir_x = dx / distance;
-            let dir_y = dy / distance;
+        // Avanzar por los puntos del camino; si alcanza uno sigue con el resto del paso
+        while remaining > 0.0 {
+            let Some(&(target_x, target_y)) = self.path.first() else {
+                break;
+            };
             
-            // Hacer al enemigo más rápido cuando está cerca del jugador
-            let speed_multiplier = if distance < 5.0 { 1.8 } else if distance < 10.0 { 1.4 } else { 1.0 };
-            let current_speed = self.speed * speed_multiplier;
+            let dx = target_x - self.x;
+            let dy = target_y - self.y;
+            let to_target = (dx * dx + dy * dy).sqrt();
+            if to_target < WAYPOINT_REACHED {
+                self.path.remove(0);
+                continue;
+            }
             
-            // Calcular nueva posición
-            let new_x = self.x + dir_x * current_speed * dt;
-            let new_y = self.y + dir_y * current_speed * dt;
+            let step = remaining.min(to_target);
+            let new_x = self.x + dx / to_target * step;
+            let new_y = self.y + dy / to_target * step;
+            self.facing = dy.atan2(dx);
             
-            // Verificar colisiones con paredes y mover
+            // Verificar colisiones con paredes y deslizarse por el eje libre
+            let (old_x, old_y) = (self.x, self.y);
             if self.can_move_to(new_x, self.y, world_map) {
                 self.x = new_x;
             }
             if self.can_move_to(self.x, new_y, world_map) {
                 self.y = new_y;
             }
+            
+            // Bloqueado: recalcular ya en vez de empujar contra la pared, salvo que la
+            // búsqueda acabe de fallar y haya que esperar el intervalo
+            if (self.x, self.y) == (old_x, old_y) {
+                if !self.search_failed {
+                    self.replan_timer = 0.0;
+                }
+                break;
+            }
+            remaining -= step;
+        }
+    }
+    
+    // Ve al jugador si está en su cono de visión (o muy cerca) y ninguna pared se interpone.
+    // El Stalker no tiene cono: ve en todas direcciones.
+    fn can_see(&self, player: &Player, world_map: &dyn TileMap) -> bool {
+        let dx = player.x - self.x;
+        let dy = player.y - self.y;
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance > self.archetype.sight_range {
+            return false;
+        }
+        
+        let mut angle = dy.atan2(dx) - self.facing;
+        angle = (angle + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+        let in_cone = distance <= SENSE_DISTANCE || angle.abs() <= SIGHT_HALF_ANGLE;
+        if !in_cone && self.archetype.behavior != Behavior::Stalker {
+            return false;
+        }
+        
+        has_line_of_sight(world_map, self.x, self.y, player.x, player.y)
+    }
+    
+    // Camino suavizado hasta el objetivo; en línea recta si no hay nada en medio
+    fn plan_path(&mut self, goal: (f32, f32), world_map: &dyn TileMap) {
+        let position = (self.x, self.y);
+        self.path_goal = Some(goal);
+        self.search_failed = false;
+        
+        if is_clear(world_map, position, goal, self.radius) {
+            self.path = vec![goal];
+            return;
+        }
+        
+        let start_cell = (self.x.floor() as i32, self.y.floor() as i32);
+        let goal_cell = (goal.0.floor() as i32, goal.1.floor() as i32);
+        match find_path(world_map, start_cell, goal_cell) {
+            Some(cells) => self.path = smooth_path(world_map, position, &cells, self.radius),
+            // Sin camino conocido (demasiado lejos o encerrado): perseguir en línea recta,
+            // o en otro estado descartar el objetivo y que la IA elija otro
+            None if self.ai.state() == AiState::Chase => {
+                self.path = vec![goal];
+                self.search_failed = true;
+            }
+            None => {
+                self.path.clear();
+                self.path_goal = None;
+                self.ai.goal_unreachable();
+            }
+        }
+    }
+    
+    /// Si el ruido le llega, va a investigar de dónde vino
+    pub fn hear(&mut self, noise: &Noise, field: &NoiseField) {
+        let level = field.level_at(self.x, self.y);
+        if self.active && level > 0.0 && level >= self.archetype.hearing_threshold {
+            self.ai.hear_noise(noise.x, noise.y);
         }
     }
     
+    pub fn state(&self) -> AiState {
+        self.ai.state()
+    }
+    
+    /// Cambios de estado de la IA desde la última llamada, para el audio y la interfaz
+    pub fn take_events(&mut self) -> Vec<AiEvent> {
+        self.ai.take_events()
+    }
+    
+    /// Puntos que le faltan por recorrer, para depurar en el minimapa
+    pub fn path(&self) -> &[(f32, f32)] {
+        &self.path
+    }
+    
     pub fn check_player_collision(&self, player: &Player) -> bool {
         if !self.alive || !self.active {
             return false;
@@ -166,17 +379,15 @@ impl Enemy {
         (dx * dx + dy * dy).sqrt()
     }
     
-    fn can_move_to(&self, x: f32, y: f32, world_map: &[[u8; 40]; 30]) -> bool {
-        let map_x = x as usize;
-        let map_y = y as usize;
-        
-        // Verificar límites
-        if map_x >= 40 || map_y >= 30 {
-            return false;
-        }
-        
-        // El enemigo puede moverse por espacios vacíos (0) y por la salida (3)
-        world_map[map_y][map_x] == 0 || world_map[map_y][map_x] == 3
+    // El cuerpo ocupa un cuadrado de lado 2 * radius alrededor de (x, y)
+    fn can_move_to(&self, x: f32, y: f32, world_map: &dyn TileMap) -> bool {
+        [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+            .iter()
+            .all(|&(sx, sy)| Self::is_walkable(world_map, x + sx * self.radius, y + sy * self.radius))
+    }
+    
+    fn is_walkable(world_map: &dyn TileMap, x: f32, y: f32) -> bool {
+        world_map.tile_at(x, y).is_some_and(|tile| !tile.is_solid_for_enemy())
     }
     
     pub fn reset(&mut self) {
@@ -186,175 +397,75 @@ impl Enemy {
         self.active = false;
     }
     
-    pub fn deactivate(&mut self) {
-        self.active = false;
-        self.alive = false;
-    }
-    
-    // Nueva función para verificar si hay línea de vista clara al jugador
-    fn has_line_of_sight(&self, player: &Player, world_map: &[[u8; 40]; 30]) -> bool {
-        let dx = player.x - self.x;
-        let dy = player.y - self.y;
-        let distance = (dx * dx + dy * dy).sqrt();
-        
-        if distance == 0.0 {
-            return true;
-        }
-        
-        // Normalizar la dirección
-        let dir_x = dx / distance;
-        let dir_y = dy / distance;
-        
-        // Usar pasos más pequeños para mayor precisión
-        let step_size = 0.1;
-        let num_steps = (distance / step_size) as i32;
-        
-        // Verificar cada punto a lo largo de la línea
-        for i in 1..num_steps {
-            let check_x = self.x + dir_x * step_size * i as f32;
-            let check_y = self.y + dir_y * step_size * i as f32;
-            
-            let map_x = check_x as usize;
-            let map_y = check_y as usize;
-            
-            // Si está fuera de límites, no hay línea de vista
-            if map_x >= 40 || map_y >= 30 {
-                return false;
-            }
-            
-            // Si hay una pared en el camino, no hay línea de vista
-            let cell_value = world_map[map_y][map_x];
-            if cell_value == 1 || cell_value == 2 { // Paredes normales y con sangre
-                return false;
-            }
-        }
-        
-        true
-    }
-    
-    // Función para renderizar el enemigo en el mundo 3D con oclusión
-    pub fn render_in_world(&self, player: &Player, screen_width: f32, screen_height: f32, world_map: &[[u8; 40]; 30]) {
+    // Sprite del enemigo para el renderizador; la oclusión la resuelve el buffer de profundidad
+    pub fn sprite(&self, player: &Player) -> Option<Sprite<'_>> {
         if !self.alive || !self.active {
-            return;
+            return None;
         }
         
-        // Calcular posición relativa al jugador
-        let dx = self.x - player.x;
-        let dy = self.y - player.y;
-        let distance = (dx * dx + dy * dy).sqrt();
+        let texture = self.texture.as_ref()?;
+        let distance = self.get_distance_to_player(player);
         
         // No renderizar si está muy lejos
         if distance > 20.0 {
-            return;
-        }
-        
-        // Verificar si hay línea de vista clara
-        if !self.has_line_of_sight(player, world_map) {
-            return; // No renderizar si hay paredes en el camino
-        }
-        
-        // Calcular ángulo desde el jugador
-        let angle_to_enemy = dy.atan2(dx);
-        let player_angle = player.angle;
-        let mut relative_angle = angle_to_enemy - player_angle;
-        
-        // Normalizar ángulo
-        while relative_angle > std::f32::consts::PI {
-            relative_angle -= 2.0 * std::f32::consts::PI;
-        }
-        while relative_angle < -std::f32::consts::PI {
-            relative_angle += 2.0 * std::f32::consts::PI;
-        }
-        
-        // Campo de visión
-        let fov = std::f32::consts::PI / 3.0;
-        
-        // Verificar si está dentro del campo de visión
-        if relative_angle.abs() <= fov / 2.0 {
-            // Calcular posición en pantalla
-            let screen_x = screen_width / 2.0 + (relative_angle / (fov / 2.0)) * (screen_width / 2.0);
-            
-            // Tamaño basado en distancia
-            let sprite_size = (screen_height / distance) * self.size;
-            let sprite_y = (screen_height - sprite_size) / 2.0;
-            
-            // Renderizar enemigo
-            if let Some(texture) = &self.texture {
-                // Efecto de parpadeo cuando está muy cerca
-                let alpha = if distance < 3.0 {
-                    let flicker = (get_time() * 10.0).sin() * 0.3 + 0.7;
-                    flicker.max(0.4)
-                } else {
-                    1.0
-                };
-                
-                draw_texture_ex(
-                    texture,
-                    screen_x - sprite_size / 2.0,
-                    sprite_y,
-                    Color::from_rgba(255, 255, 255, (alpha * 255.0) as u8),
-                    DrawTextureParams {
-                        dest_size: Some(vec2(sprite_size, sprite_size)),
-                        ..Default::default()
-                    },
-                );
-            } else {
-                // Enemigo generado por código si no hay textura
-                self.draw_generated_enemy(screen_x, sprite_y, sprite_size, distance);
-            }
+            return None;
         }
-    }
-    
-    fn draw_generated_enemy(&self, x: f32, y: f32, size: f32, distance: f32) {
-        // Crear un enemigo terrorífico usando formas básicas
-        let half_size = size / 2.0;
         
-        // Efecto de parpadeo cuando está cerca
+        // Efecto de parpadeo cuando está muy cerca
         let alpha = if distance < 3.0 {
-            let flicker = (get_time() * 10.0).sin() * 0.3 + 0.7;
-            (flicker.max(0.4) * 255.0) as u8
+            let flicker = (get_time() * 10.0).sin() as f32 * 0.3 + 0.7;
+            flicker.max(0.4)
         } else {
-            255
+            1.0
         };
         
-        // Cuerpo principal (sombra oscura más grande)
-        draw_ellipse(x, y + size * 0.2, size * 0.9, size * 0.8, 0.0, 
-            Color::from_rgba(10, 10, 10, alpha));
-        
-        // Ojos rojos brillantes más grandes y amenazantes
-        let eye_size = size * 0.12;
-        let eye_glow = (get_time() * 5.0).sin() * 0.2 + 0.8;
-        let eye_color = Color::from_rgba(
-            (255.0 * eye_glow) as u8, 
-            (30.0 * eye_glow) as u8, 
-            (30.0 * eye_glow) as u8, 
-            alpha
-        );
-        
-        draw_circle(x - half_size * 0.3, y + size * 0.15, eye_size, eye_color);
-        draw_circle(x + half_size * 0.3, y + size * 0.15, eye_size, eye_color);
-        
-        // Pupilas más pequeñas y brillantes
-        draw_circle(x - half_size * 0.3, y + size * 0.15, eye_size * 0.3, 
-            Color::from_rgba(255, 255, 200, alpha));
-        draw_circle(x + half_size * 0.3, y + size * 0.15, eye_size * 0.3, 
-            Color::from_rgba(255, 255, 200, alpha));
-        
-        // Boca amenazante
-        draw_ellipse(x, y + size * 0.5, size * 0.4, size * 0.2, 0.0, 
-            Color::from_rgba(80, 0, 0, alpha));
-        
-        // Efecto de flotación más pronunciado
-        let float_offset = (get_time() * 4.0).sin() * size as f64 * 0.15;
-        draw_circle(x, y + size * 0.85 + float_offset as f32, size * 0.15,
-            Color::from_rgba(20, 20, 20, (alpha as f32 * 0.8) as u8));
-        
-        // Aura siniestra cuando está muy cerca
-        if distance < 5.0 {
-            let aura_size = size * 1.5;
-            let aura_alpha = ((5.0 - distance) / 5.0 * 50.0) as u8;
-            draw_circle(x, y + size * 0.4, aura_size, 
-                Color::from_rgba(100, 0, 0, aura_alpha));
+        let [r, g, b] = self.archetype.tint;
+        Some(Sprite {
+            x: self.x,
+            y: self.y,
+            texture,
+            height: self.size,
+            tint: Color::new(r, g, b, alpha),
+        })
+    }
+}
+
+/// Carga el sprite de cada tipo de enemigo, indexado por nombre de archivo
+pub async fn load_enemy_textures() -> HashMap<&'static str, Texture2D> {
+    let mut textures = HashMap::new();
+    for archetype in ARCHETYPES {
+        if !textures.contains_key(archetype.sprite) {
+            textures.insert(archetype.sprite, load_enemy_texture(archetype.sprite).await);
         }
     }
-}
\ No newline at end of file
+    textures
+}
+
+async fn load_enemy_texture(file: &str) -> Texture2D {
+    println!("Intentando cargar imagen del enemigo {}...", file);
+    
+    let paths = [
+        file.to_string(),
+        format!("./{}", file),
+        format!("../{}", file),
+        format!("assets/images/{}", file),
+        format!("./assets/images/{}", file),
+        format!("assets/{}", file),
+    ];
+    
+    for path in &paths {
+        if std::path::Path::new(path).exists() {
+            match load_texture(path).await {
+                Ok(texture) => {
+                    println!("  ✓ Imagen enemigo cargada: {}", path);
+                    return texture;
+                }
+                Err(e) => println!("  ✗ Error cargando {}: {}", path, e),
+            }
+        }
+    }
+    
+    println!("  ✗ No se pudo encontrar {} en ninguna ubicación", file);
+    println!("  → Se usará un enemigo generado por código");
+    Texture2D::from_image(&generate_enemy_texture())
+}
@@ -0,0 +1,291 @@
+// Máquina de estados del enemigo. Decide a dónde quiere ir y qué tan rápido; el
+// enemigo se encarga de ver, oír y de caminar hasta ese punto con pathfinding.
+//
+//   Wander ──ruido──> Investigate ──llega──> Search ──tiempo──> Wander (se rinde)
+//      └───────────── ve al jugador (desde cualquier estado) ──> Chase
+//   Chase ──lo pierde de vista──> Search (empezando por la última posición conocida)
+
+/// Qué está haciendo el enemigo
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AiState {
+    /// Sin saber del jugador: recorre su ruta de patrulla
+    Wander,
+    /// Va hacia un ruido que escuchó
+    Investigate,
+    /// Ve al jugador y lo persigue
+    Chase,
+    /// Lo perdió: revisa la última posición conocida y los alrededores
+    Search,
+}
+
+impl AiState {
+    pub fn name(self) -> &'static str {
+        match self {
+            AiState::Wander => "wander",
+            AiState::Investigate => "investigate",
+            AiState::Chase => "chase",
+            AiState::Search => "search",
+        }
+    }
+}
+
+/// Cambio de estado, para que el audio y la interfaz reaccionen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AiEvent {
+    pub from: AiState,
+    pub to: AiState,
+}
+
+impl AiEvent {
+    /// Dejó de buscar y volvió a patrullar
+    pub fn gave_up(&self) -> bool {
+        self.from == AiState::Search && self.to == AiState::Wander
+    }
+}
+
+// Distancia a la que se considera alcanzado un objetivo
+const ARRIVE_DISTANCE: f32 = 0.6;
+// Pausa en cada punto de la patrulla
+const PATROL_PAUSE: f32 = 1.5;
+// Tiempo máximo yendo hacia un ruido antes de ponerse a buscar
+const INVESTIGATE_TIMEOUT: f32 = 15.0;
+// Segundos que sigue hacia donde vio al jugador antes de pasar a buscar
+const CHASE_MEMORY: f32 = 1.0;
+// Duración de la búsqueda antes de rendirse, y distancia de los puntos que revisa
+const SEARCH_TIME: f32 = 10.0;
+const SEARCH_RADIUS: f32 = 3.0;
+
+pub struct EnemyAi {
+    state: AiState,
+    // Segundos en el estado actual (o desde que perdió de vista al jugador, en Chase)
+    state_timer: f32,
+    patrol: Vec<(f32, f32)>,
+    patrol_index: usize,
+    pause_timer: f32,
+    // Ruido que investiga, o última posición donde vio al jugador
+    target: (f32, f32),
+    search_points: Vec<(f32, f32)>,
+    search_index: usize,
+    events: Vec<AiEvent>,
+}
+
+impl EnemyAi {
+    pub fn new() -> Self {
+        Self {
+            state: AiState::Wander,
+            state_timer: 0.0,
+            patrol: Vec::new(),
+            patrol_index: 0,
+            pause_timer: 0.0,
+            target: (0.0, 0.0),
+            search_points: Vec::new(),
+            search_index: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Empieza de cero con una ruta de patrulla nueva
+    pub fn reset(&mut self, patrol: Vec<(f32, f32)>) {
+        *self = Self { patrol, ..Self::new() };
+    }
+
+    pub fn state(&self) -> AiState {
+        self.state
+    }
+
+    /// Eventos de cambio de estado desde la última llamada
+    pub fn take_events(&mut self) -> Vec<AiEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Escuchó un ruido en (x, y). Mientras persigue no se distrae.
+    pub fn hear_noise(&mut self, x: f32, y: f32) {
+        if self.state == AiState::Chase {
+            return;
+        }
+        self.target = (x, y);
+        self.set_state(AiState::Investigate);
+    }
+
+    /// Avanza la máquina de estados. `player` es la posición del jugador si el enemigo lo ve.
+    pub fn update(&mut self, dt: f32, position: (f32, f32), player: Option<(f32, f32)>) {
+        self.state_timer += dt;
+
+        if let Some(player) = player {
+            self.target = player;
+            if self.state == AiState::Chase {
+                self.state_timer = 0.0;
+            } else {
+                self.set_state(AiState::Chase);
+            }
+            return;
+        }
+
+        match self.state {
+            AiState::Wander => {
+                if self.goal().is_some_and(|goal| distance(position, goal) < ARRIVE_DISTANCE) {
+                    self.pause_timer += dt;
+                    if self.pause_timer >= PATROL_PAUSE {
+                        self.next_patrol_point();
+                    }
+                }
+            }
+            AiState::Investigate => {
+                if distance(position, self.target) < ARRIVE_DISTANCE || self.state_timer >= INVESTIGATE_TIMEOUT {
+                    self.start_search();
+                }
+            }
+            AiState::Chase => {
+                // Sin verlo: sigue hacia la última posición un momento y luego busca
+                if self.state_timer >= CHASE_MEMORY {
+                    self.start_search();
+                }
+            }
+            AiState::Search => {
+                if self.state_timer >= SEARCH_TIME {
+                    self.set_state(AiState::Wander);
+                } else if distance(position, self.search_points[self.search_index]) < ARRIVE_DISTANCE {
+                    self.next_search_point();
+                }
+            }
+        }
+    }
+
+    /// Punto al que quiere llegar, o None si se queda quieto
+    pub fn goal(&self) -> Option<(f32, f32)> {
+        match self.state {
+            AiState::Wander => self.patrol.get(self.patrol_index).copied(),
+            AiState::Investigate | AiState::Chase => Some(self.target),
+            AiState::Search => self.search_points.get(self.search_index).copied(),
+        }
+    }
+
+    /// El objetivo actual no se puede alcanzar: pasar al siguiente
+    pub fn goal_unreachable(&mut self) {
+        match self.state {
+            AiState::Wander => self.next_patrol_point(),
+            AiState::Investigate => self.start_search(),
+            AiState::Search => self.next_search_point(),
+            // Persiguiendo, el objetivo se mueve; se vuelve a intentar en el próximo cálculo
+            AiState::Chase => {}
+        }
+    }
+
+    /// Multiplicador de velocidad del estado actual
+    pub fn speed_factor(&self) -> f32 {
+        match self.state {
+            AiState::Wander => 0.5,
+            AiState::Investigate => 0.8,
+            AiState::Chase => 1.0,
+            AiState::Search => 0.7,
+        }
+    }
+
+    fn set_state(&mut self, state: AiState) {
+        if state != self.state {
+            self.events.push(AiEvent { from: self.state, to: state });
+            self.state = state;
+        }
+        self.state_timer = 0.0;
+    }
+
+    fn next_patrol_point(&mut self) {
+        self.pause_timer = 0.0;
+        if !self.patrol.is_empty() {
+            self.patrol_index = (self.patrol_index + 1) % self.patrol.len();
+        }
+    }
+
+    // Revisa primero el objetivo y después una cruz de puntos a su alrededor
+    fn start_search(&mut self) {
+        let (x, y) = self.target;
+        self.search_points = vec![
+            (x, y),
+            (x + SEARCH_RADIUS, y),
+            (x, y + SEARCH_RADIUS),
+            (x - SEARCH_RADIUS, y),
+            (x, y - SEARCH_RADIUS),
+        ];
+        self.search_index = 0;
+        self.set_state(AiState::Search);
+    }
+
+    fn next_search_point(&mut self) {
+        self.search_index = (self.search_index + 1) % self.search_points.len();
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.1;
+
+    fn run(ai: &mut EnemyAi, seconds: f32, position: (f32, f32), player: Option<(f32, f32)>) {
+        for _ in 0..(seconds / DT).round() as usize {
+            ai.update(DT, position, player);
+        }
+    }
+
+    fn states(events: &[AiEvent]) -> Vec<AiState> {
+        events.iter().map(|event| event.to).collect()
+    }
+
+    #[test]
+    fn chases_then_searches_then_gives_up() {
+        let mut ai = EnemyAi::new();
+        ai.reset(vec![(1.0, 1.0), (5.0, 1.0)]);
+
+        run(&mut ai, 1.0, (3.0, 3.0), Some((8.0, 3.0)));
+        assert_eq!(ai.state(), AiState::Chase);
+        assert_eq!(ai.goal(), Some((8.0, 3.0)));
+
+        // Lo pierde de vista: busca empezando por la última posición conocida
+        run(&mut ai, CHASE_MEMORY + DT, (4.0, 3.0), None);
+        assert_eq!(ai.state(), AiState::Search);
+        assert_eq!(ai.goal(), Some((8.0, 3.0)));
+
+        run(&mut ai, SEARCH_TIME + DT, (4.0, 3.0), None);
+        assert_eq!(ai.state(), AiState::Wander);
+
+        let events = ai.take_events();
+        assert_eq!(states(&events), vec![AiState::Chase, AiState::Search, AiState::Wander]);
+        assert!(events[2].gave_up());
+        assert!(ai.take_events().is_empty());
+    }
+
+    #[test]
+    fn investigates_noise_and_searches_on_arrival() {
+        let mut ai = EnemyAi::new();
+        ai.reset(vec![(1.0, 1.0)]);
+
+        ai.hear_noise(6.0, 2.0);
+        assert_eq!(ai.state(), AiState::Investigate);
+        assert_eq!(ai.goal(), Some((6.0, 2.0)));
+
+        run(&mut ai, DT, (6.2, 2.0), None);
+        assert_eq!(ai.state(), AiState::Search);
+
+        // Mientras persigue, los ruidos no lo distraen
+        run(&mut ai, DT, (6.2, 2.0), Some((7.0, 2.0)));
+        ai.hear_noise(0.0, 0.0);
+        assert_eq!(ai.state(), AiState::Chase);
+    }
+
+    #[test]
+    fn patrols_in_a_loop() {
+        let mut ai = EnemyAi::new();
+        ai.reset(vec![(1.0, 1.0), (5.0, 1.0)]);
+
+        assert_eq!(ai.goal(), Some((1.0, 1.0)));
+        run(&mut ai, PATROL_PAUSE + DT, (1.0, 1.0), None);
+        assert_eq!(ai.goal(), Some((5.0, 1.0)));
+        run(&mut ai, PATROL_PAUSE + DT, (5.0, 1.0), None);
+        assert_eq!(ai.goal(), Some((1.0, 1.0)));
+        assert!(ai.take_events().is_empty());
+    }
+}
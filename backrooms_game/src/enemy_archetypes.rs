@@ -0,0 +1,113 @@
+// Tipos de enemigo. Cada nivel indica en su EnemyParams qué tipos aparecen y
+// cuántos; para agregar un tipo basta con un EnemyArchetype nuevo y sumarlo a ARCHETYPES.
+
+/// Cómo se comporta cuando no sabe dónde está el jugador
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Behavior {
+    /// Patrulla alrededor de donde apareció y llega atraído por el jugador
+    Hunter,
+    /// Se queda quieto donde apareció hasta ver u oír algo
+    Lurker,
+    /// Como Lurker, pero ve en todas direcciones y se congela mientras el jugador lo está mirando
+    Stalker,
+}
+
+pub struct EnemyArchetype {
+    pub name: &'static str,
+    pub speed: f32,
+    /// Persiguiendo: (distancia al jugador, multiplicador de velocidad), de menor a mayor
+    /// distancia. Se usa el primer tramo cuya distancia supera la actual; más lejos, 1.0.
+    pub chase_speed_curve: &'static [(f32, f32)],
+    pub catch_distance: f32,
+    /// Altura del sprite en el mundo (1.0 = una pared)
+    pub size: f32,
+    /// Radio del cuerpo para colisiones y para decidir por dónde cabe
+    pub radius: f32,
+    /// Distancia máxima a la que ve al jugador; 0 = ciego
+    pub sight_range: f32,
+    /// Intensidad mínima que tiene que tener un ruido al llegarle para oírlo
+    pub hearing_threshold: f32,
+    /// Archivo de imagen del sprite; si no se encuentra se usa uno generado, teñido con `tint`
+    pub sprite: &'static str,
+    pub tint: [f32; 3],
+    /// Sonido en bucle que se oye al acercarse; si no está cargado se usa "enemigoBackground"
+    pub sound: &'static str,
+    pub behavior: Behavior,
+}
+
+impl EnemyArchetype {
+    /// Multiplicador de velocidad persiguiendo a `distance` celdas del jugador
+    pub fn chase_speed(&self, distance: f32) -> f32 {
+        self.chase_speed_curve
+            .iter()
+            .find(|&&(limit, _)| distance < limit)
+            .map_or(1.0, |&(_, multiplier)| multiplier)
+    }
+}
+
+// El enemigo original: patrulla, ve bien y acelera al acercarse
+pub static SHADOW: EnemyArchetype = EnemyArchetype {
+    name: "Sombra",
+    speed: 2.8,
+    chase_speed_curve: &[(5.0, 1.8), (10.0, 1.4)],
+    catch_distance: 1.0,
+    size: 0.95,
+    radius: 0.3,
+    sight_range: 14.0,
+    hearing_threshold: 1.0,
+    sprite: "enemigo.png",
+    tint: [1.0, 1.0, 1.0],
+    sound: "enemigoBackground",
+    behavior: Behavior::Hunter,
+};
+
+// Espera inmóvil, ve lejos y oye poco; cuando te ve arranca muy rápido en distancias cortas
+pub static LURKER: EnemyArchetype = EnemyArchetype {
+    name: "Acechador",
+    speed: 2.0,
+    chase_speed_curve: &[(8.0, 2.2)],
+    catch_distance: 1.1,
+    size: 1.1,
+    radius: 0.35,
+    sight_range: 20.0,
+    hearing_threshold: 3.0,
+    sprite: "enemigo_lurker.png",
+    tint: [0.6, 0.7, 1.0],
+    sound: "enemy_lurker",
+    behavior: Behavior::Lurker,
+};
+
+// Ciego pero con un oído finísimo y rápido: agacharse es la única forma de pasarlo
+pub static HOWLER: EnemyArchetype = EnemyArchetype {
+    name: "Aullador",
+    speed: 3.4,
+    chase_speed_curve: &[],
+    catch_distance: 0.9,
+    size: 0.7,
+    radius: 0.25,
+    sight_range: 0.0,
+    hearing_threshold: 0.0,
+    sprite: "enemigo_howler.png",
+    tint: [1.0, 0.75, 0.55],
+    sound: "enemy_howler",
+    behavior: Behavior::Hunter,
+};
+
+// Una figura sonriente que no se mueve si la miras, y se te echa encima en cuanto apartas la vista
+pub static STALKER: EnemyArchetype = EnemyArchetype {
+    name: "Sonriente",
+    speed: 4.5,
+    chase_speed_curve: &[],
+    catch_distance: 1.0,
+    size: 1.0,
+    radius: 0.3,
+    sight_range: 30.0,
+    hearing_threshold: 2.0,
+    sprite: "enemigo_stalker.png",
+    tint: [0.95, 0.95, 0.85],
+    sound: "enemy_stalker",
+    behavior: Behavior::Stalker,
+};
+
+/// Todos los tipos, para cargar sus sprites al inicio
+pub static ARCHETYPES: [&EnemyArchetype; 4] = [&SHADOW, &LURKER, &HOWLER, &STALKER];
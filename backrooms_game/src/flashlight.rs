@@ -0,0 +1,175 @@
+// Linterna del jugador: ilumina un cono hacia donde mira y gasta batería mientras
+// está prendida. Con poca carga, o con un enemigo cerca, parpadea. Las baterías que
+// se encuentran por el mapa la recargan.
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+// Segundos de luz con la batería llena, y carga que devuelve cada batería recogida
+const BATTERY_LIFE: f32 = 180.0;
+pub const BATTERY_PICKUP_CHARGE: f32 = 0.5;
+// Con menos carga que esto la luz se debilita (hasta MIN_DIM) y empieza a parpadear
+const LOW_BATTERY: f32 = 0.2;
+const MIN_DIM: f32 = 0.4;
+// Un enemigo a menos de esta distancia hace parpadear la luz, más cuanto más cerca
+const ENEMY_FLICKER_RANGE: f32 = 8.0;
+// Parpadeos por segundo con la máxima inquietud, y duración (min..max) de cada uno
+const FLICKER_RATE: f32 = 5.0;
+const FLICKER_DURATION: (f32, f32) = (0.04, 0.15);
+// Haz: ángulo desde el centro de la vista con luz plena, ángulo donde ya no llega,
+// y distancia a la que la luz cae a la mitad
+const BEAM_INNER_ANGLE: f32 = 0.15;
+const BEAM_OUTER_ANGLE: f32 = 0.4;
+const BEAM_RANGE: f32 = 6.0;
+
+pub struct Flashlight {
+    pub on: bool,
+    /// Carga de 0 (vacía) a 1 (llena)
+    pub battery: f32,
+    // Brillo del parpadeo en curso y cuánto le queda
+    flicker_level: f32,
+    flicker_timer: f32,
+    rng: StdRng,
+}
+
+impl Flashlight {
+    /// Prendida y con la batería llena. Los parpadeos salen de `seed`, así una partida
+    /// con la misma semilla parpadea igual.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            on: true,
+            battery: 1.0,
+            flicker_level: 1.0,
+            flicker_timer: 0.0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.on = !self.on;
+    }
+
+    pub fn recharge(&mut self, amount: f32) {
+        self.battery = (self.battery + amount).min(1.0);
+    }
+
+    /// Gasta batería si está prendida y decide los parpadeos. `nearest_enemy` es la
+    /// distancia al enemigo activo más cercano.
+    pub fn update(&mut self, dt: f32, nearest_enemy: Option<f32>) {
+        if self.on {
+            self.battery = (self.battery - dt / BATTERY_LIFE).max(0.0);
+        }
+
+        if self.flicker_timer > 0.0 {
+            self.flicker_timer -= dt;
+            if self.flicker_timer <= 0.0 {
+                self.flicker_level = 1.0;
+            }
+            return;
+        }
+
+        // Probabilidad de al menos un parpadeo en dt segundos, como los sustos del director
+        let unrest = self.unrest(nearest_enemy);
+        if unrest > 0.0 && self.rng.gen::<f32>() < 1.0 - (-FLICKER_RATE * unrest * dt).exp() {
+            // Cuanto más inquieta, más se apaga en cada parpadeo
+            self.flicker_level = self.rng.gen_range(0.0..1.0 - unrest * 0.7);
+            self.flicker_timer = self.rng.gen_range(FLICKER_DURATION.0..FLICKER_DURATION.1);
+        }
+    }
+
+    /// Fracción de luz que da ahora mismo: 0 apagada o sin batería, 1 a pleno
+    pub fn intensity(&self) -> f32 {
+        if !self.on || self.battery <= 0.0 {
+            return 0.0;
+        }
+        let dim = MIN_DIM + (1.0 - MIN_DIM) * (self.battery / LOW_BATTERY).min(1.0);
+        dim * self.flicker_level
+    }
+
+    // Qué tanto parpadea, de 0 a 1: por batería baja o por un enemigo cerca, lo que sea mayor
+    fn unrest(&self, nearest_enemy: Option<f32>) -> f32 {
+        let battery = (1.0 - self.battery / LOW_BATTERY).max(0.0);
+        let enemy = nearest_enemy.map_or(0.0, |distance| (1.0 - distance / ENEMY_FLICKER_RANGE).max(0.0));
+        battery.max(enemy)
+    }
+}
+
+/// Fracción de la luz de la linterna que llega a un punto a `distance` celdas del
+/// jugador y a `angle` radianes del centro de la vista
+pub fn beam(angle: f32, distance: f32) -> f32 {
+    let edge = ((angle.abs() - BEAM_INNER_ANGLE) / (BEAM_OUTER_ANGLE - BEAM_INNER_ANGLE)).clamp(0.0, 1.0);
+    let cone = 1.0 - edge * edge * (3.0 - 2.0 * edge);
+    cone / (1.0 + (distance / BEAM_RANGE).powi(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn battery_drains_only_while_on_and_recharges() {
+        let mut flashlight = Flashlight::new(7);
+        flashlight.toggle();
+        flashlight.update(60.0, None);
+        assert_eq!(flashlight.battery, 1.0);
+        assert_eq!(flashlight.intensity(), 0.0);
+
+        flashlight.toggle();
+        for _ in 0..(BATTERY_LIFE as usize + 1) {
+            flashlight.update(1.0, None);
+        }
+        assert_eq!(flashlight.battery, 0.0);
+        assert_eq!(flashlight.intensity(), 0.0);
+
+        flashlight.recharge(BATTERY_PICKUP_CHARGE);
+        flashlight.recharge(BATTERY_PICKUP_CHARGE);
+        flashlight.recharge(BATTERY_PICKUP_CHARGE);
+        assert_eq!(flashlight.battery, 1.0);
+    }
+
+    #[test]
+    fn flickers_only_when_uneasy() {
+        // Cuadros de 5 segundos a 60 FPS en medio de un parpadeo
+        let dim_frames = |flashlight: &mut Flashlight, nearest_enemy: Option<f32>| {
+            (0..300)
+                .filter(|_| {
+                    flashlight.update(1.0 / 60.0, nearest_enemy);
+                    flashlight.flicker_level < 1.0
+                })
+                .count()
+        };
+
+        let mut calm = Flashlight::new(7);
+        assert_eq!(dim_frames(&mut calm, Some(ENEMY_FLICKER_RANGE + 1.0)), 0);
+
+        let mut scared = Flashlight::new(7);
+        assert!(dim_frames(&mut scared, Some(1.0)) > 0);
+
+        let mut low = Flashlight::new(7);
+        low.battery = LOW_BATTERY * 0.25;
+        assert!(dim_frames(&mut low, None) > 0);
+    }
+
+    #[test]
+    fn same_seed_flickers_the_same() {
+        let flicker = |seed: u64| {
+            let mut flashlight = Flashlight::new(seed);
+            (0..300)
+                .map(|_| {
+                    flashlight.update(1.0 / 60.0, Some(1.0));
+                    flashlight.flicker_level
+                })
+                .collect::<Vec<f32>>()
+        };
+        assert_eq!(flicker(3), flicker(3));
+        assert_ne!(flicker(3), flicker(4));
+    }
+
+    #[test]
+    fn beam_lights_a_cone_that_fades_with_distance() {
+        assert_eq!(beam(0.0, 0.0), 1.0);
+        assert!(beam(0.0, 2.0) > beam(0.0, 8.0));
+        assert!(beam(BEAM_INNER_ANGLE, 2.0) > beam(0.3, 2.0));
+        assert_eq!(beam(BEAM_OUTER_ANGLE, 2.0), 0.0);
+        assert_eq!(beam(-1.0, 2.0), 0.0);
+    }
+}
@@ -1,27 +1,65 @@
 extern crate rand;
-use rand::{Rng, seq::SliceRandom};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use crate::campaign::{LevelDef, CAMPAIGN, ENDLESS};
+use crate::difficulty::Difficulty;
+use crate::input::{Bindings, Input};
+use crate::settings::Settings;
+use crate::chunked_world::{ChunkedWorld, CHUNK_SIZE};
+use crate::director::{Director, DirectorEvent, Tension};
+use crate::enemy_archetypes::EnemyArchetype;
+use crate::level_file::Level;
+use crate::lighting::{CeilingLight, LightMap};
+use crate::map_generators::{add_batteries, add_bloody_walls, place_lights, GeneratorKind};
+use crate::map_validation::{connect_regions, validate};
+use crate::tile::Tile;
+use crate::world_map::{TileMap, WorldMap};
+
+// Tamaño mínimo de mapa que admite el generador
+const MIN_MAP_SIZE: usize = 16;
+// Celda donde aparece el jugador
+pub const SPAWN_CELL: (usize, usize) = (2, 2);
+// Segundos antes de que se active el enemigo en niveles de archivo que no lo indican
+pub const DEFAULT_ENEMY_DELAY: f32 = 10.0;
+// Intentos de generación antes de quedarse con el último mapa
+const MAX_GENERATION_ATTEMPTS: usize = 5;
+// Duración del fundido al pasar de un nivel de la campaña al siguiente
+pub const LEVEL_TRANSITION_TIME: f32 = 2.5;
+// Archivo donde se anota el resultado de cada partida
+const RESULTS_FILE: &str = "runs.txt";
+// Duración del aviso cuando el enemigo ve al jugador
+pub const ENEMY_ALERT_TIME: f32 = 1.2;
+// Modo infinito: cada cuántos segundos aparece (o se mueve) la salida, y a qué distancia
+// del jugador; cuanto más tiempo se sobrevive, más lejos aparece
+const ENDLESS_EXIT_INTERVAL: (f32, f32) = (60.0, 120.0);
+const ENDLESS_EXIT_BASE_DISTANCE: f32 = 24.0;
+const ENDLESS_EXIT_DISTANCE_PER_SECOND: f32 = 0.2;
 
 #[derive(PartialEq)]
 pub enum Screen {
     Menu,
+    /// Opciones de cámara y mouse, se entra desde el menú
+    Options,
+    /// Cambiar las teclas y botones de cada acción, se entra desde las opciones
+    Controls,
     Game,
     GameOver,
 }
 
 pub struct GameState {
     pub current_screen: Screen,
-    pub world_map: [[u8; 40]; 30],
+    pub world_map: WorldMap,
     pub escaped: bool,
     pub victory_sound_played: bool,
     pub screamer_triggered: bool,
     pub screamer_timer: f32,
     pub screamer_active: bool,
     pub exit_position: (usize, usize),
+    // Pasos del camino más corto del inicio a la salida
+    pub exit_path_length: usize,
     // Campos para el screamer aleatorio
     pub random_screamer_triggered: bool,
     pub random_screamer_active: bool,
     pub random_screamer_timer: f32,
-    pub random_screamer_cooldown: f32,
     pub game_timer: f32,
     // Nuevos campos para Game Over
     pub game_over: bool,
@@ -29,42 +67,101 @@ pub struct GameState {
     pub death_screamer_active: bool,
     pub death_screamer_timer: f32,
     pub death_screamer_sound_played: bool,
+    /// Decide cuándo aparecen los enemigos y cuándo hay sustos
+    pub director: Director,
+    pub difficulty: Difficulty,
+    /// Opciones del jugador, guardadas en SETTINGS_FILE
+    pub settings: Settings,
+    /// Acciones del teclado, mouse y control en el cuadro actual
+    pub input: Input,
+    /// Partida en pausa: no avanza nada hasta volver a pausar
+    pub paused: bool,
     // Campo para controlar cuando activar el enemigo
-    pub enemy_activation_timer: f32,
     pub enemy_should_activate: bool,
+    // Reglas del enemigo: segundos hasta activarlo (None = sin enemigo) y celdas de aparición
+    pub enemy_activation_delay: Option<f32>,
+    pub enemy_spawn_points: Vec<(usize, usize)>,
+    // Posición (x, y) y ángulo donde empieza el jugador
+    pub player_start: (f32, f32, f32),
+    pub level_name: String,
+    // Nivel de la campaña en juego (índice en CAMPAIGN) y tiempo restante del fundido de entrada
+    pub level_index: usize,
+    pub level_transition_timer: f32,
+    // Aviso en pantalla de que el enemigo acaba de ver al jugador
+    pub enemy_alert_timer: f32,
+    // Generador del mapa actual; forced_generator (línea de comandos) ignora el que elija el nivel
+    pub generator: GeneratorKind,
+    pub forced_generator: Option<GeneratorKind>,
+    // Mundo infinito por chunks; si existe reemplaza a world_map durante la partida
+    pub endless: Option<ChunkedWorld>,
+    /// Luz de las lámparas del techo en el mapa en juego
+    pub light_map: LightMap,
+    // Lámparas del mapa fijo; su mapa de luz se arma recién al empezar a jugarlo
+    lights: Vec<CeilingLight>,
+    next_endless_exit_time: f32,
+    // Nivel cargado desde archivo; si existe se repite en cada partida en vez de generar
+    loaded_level: Option<Level>,
+    // Semilla de la partida actual; con fixed_seed (línea de comandos) todas las partidas la repiten
+    pub seed: u64,
+    fixed_seed: Option<u64>,
+    // Único RNG del juego: genera el mapa y luego decide los eventos aleatorios de la partida
+    pub rng: StdRng,
 }
 
 impl GameState {
-    pub fn new() -> Self {
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or_else(rand::random);
         let mut game_state = Self {
             current_screen: Screen::Menu,
-            world_map: [[0; 40]; 30],
+            world_map: WorldMap::new(CAMPAIGN[0].generator.width, CAMPAIGN[0].generator.height, Tile::Empty),
             escaped: false,
             victory_sound_played: false,
             screamer_triggered: false,
             screamer_timer: 0.0,
             screamer_active: false,
             exit_position: (0, 0),
+            exit_path_length: 0,
             random_screamer_triggered: false,
             random_screamer_active: false,
             random_screamer_timer: 0.0,
-            random_screamer_cooldown: 0.0,
             game_timer: 0.0,
             game_over: false,
             game_over_timer: 0.0,
             death_screamer_active: false,
             death_screamer_timer: 0.0,
             death_screamer_sound_played: false,
-            enemy_activation_timer: 0.0,
+            director: Director::new(None, 1.0, seed),
+            difficulty: Difficulty::Normal,
+            settings: Settings::default(),
+            input: Input::new(Bindings::default()),
+            paused: false,
             enemy_should_activate: false,
+            enemy_activation_delay: Some(DEFAULT_ENEMY_DELAY),
+            enemy_spawn_points: Vec::new(),
+            player_start: (SPAWN_CELL.0 as f32 + 0.5, SPAWN_CELL.1 as f32 + 0.5, 0.0),
+            level_name: String::new(),
+            level_index: 0,
+            level_transition_timer: 0.0,
+            enemy_alert_timer: 0.0,
+            generator: GeneratorKind::Maze,
+            forced_generator: None,
+            endless: None,
+            light_map: LightMap::empty(),
+            lights: Vec::new(),
+            next_endless_exit_time: 0.0,
+            loaded_level: None,
+            seed,
+            fixed_seed,
+            rng: StdRng::seed_from_u64(seed),
         };
         
-        game_state.generate_world();
+        game_state.generate_level();
         game_state
     }
     
     pub fn reset(&mut self) {
         self.current_screen = Screen::Menu;
+        self.paused = false;
         self.escaped = false;
         self.victory_sound_played = false;
         self.screamer_triggered = false;
@@ -73,25 +170,275 @@ impl GameState {
         self.random_screamer_triggered = false;
         self.random_screamer_active = false;
         self.random_screamer_timer = 0.0;
-        self.random_screamer_cooldown = 0.0;
         self.game_timer = 0.0;
         self.game_over = false;
         self.game_over_timer = 0.0;
         self.death_screamer_active = false;
         self.death_screamer_timer = 0.0;
         self.death_screamer_sound_played = false;
-        self.enemy_activation_timer = 0.0;
-        self.enemy_should_activate = false;
-        self.generate_world();
+        self.level_index = 0;
+        self.level_transition_timer = 0.0;
+        self.enemy_alert_timer = 0.0;
+        self.endless = None;
+        self.seed = self.fixed_seed.unwrap_or_else(rand::random);
+        match self.loaded_level.take() {
+            Some(level) => self.load_level(level),
+            None => self.generate_level(),
+        }
+    }
+    
+    /// Nivel de la campaña en juego. Los niveles de archivo usan las reglas del primero.
+    pub fn level_def(&self) -> &'static LevelDef {
+        if self.endless.is_some() {
+            return &ENDLESS;
+        }
+        &CAMPAIGN[self.level_index]
+    }
+    
+    /// Mapa en el que se juega: el mundo infinito si está activo, si no el mapa fijo
+    pub fn map(&self) -> &dyn TileMap {
+        match &self.endless {
+            Some(world) => world,
+            None => &self.world_map,
+        }
+    }
+    
+    /// Mapa, puntos de aparición y RNG prestados a la vez, para ubicar al enemigo
+    pub fn spawn_context(&mut self) -> (&dyn TileMap, &[(usize, usize)], &mut StdRng) {
+        let map: &dyn TileMap = match &self.endless {
+            Some(world) => world,
+            None => &self.world_map,
+        };
+        (map, &self.enemy_spawn_points, &mut self.rng)
+    }
+    
+    /// Empieza una partida en el mundo infinito con la semilla actual
+    pub fn start_endless(&mut self) {
+        let mut world = ChunkedWorld::new(self.seed, &ENDLESS);
+        
+        // El chunk de origen deja libre su centro para el jugador
+        let center = CHUNK_SIZE as f32 / 2.0 + 0.5;
+        world.stream_around(center, center);
+        self.light_map = world.light_map(center, center);
+        self.endless = Some(world);
+        
+        self.player_start = (center, center, 0.0);
+        self.enemy_activation_delay = ENDLESS.enemy.delay;
+        self.enemy_spawn_points.clear();
+        self.level_name = format!("{} (semilla {})", ENDLESS.name, self.seed);
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.next_endless_exit_time = self.rng.gen_range(ENDLESS_EXIT_INTERVAL.0..ENDLESS_EXIT_INTERVAL.1);
+        println!("Modo infinito iniciado con semilla {}", self.seed);
+        
+        self.start_game();
+    }
+    
+    /// Mantiene cargados los chunks alrededor del jugador y, en el modo infinito,
+    /// hace aparecer la salida de vez en cuando
+    pub fn update_world(&mut self, player_x: f32, player_y: f32) {
+        let Some(world) = &mut self.endless else {
+            return;
+        };
+        if world.stream_around(player_x, player_y) {
+            let light_map = world.light_map(player_x, player_y);
+            let previous = std::mem::replace(&mut self.light_map, light_map);
+            self.light_map.continue_from(&previous);
+        }
+        
+        if self.game_timer >= self.next_endless_exit_time {
+            let distance = ENDLESS_EXIT_BASE_DISTANCE + self.game_timer * ENDLESS_EXIT_DISTANCE_PER_SECOND;
+            let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            let exit = world.place_exit_near(player_x + angle.cos() * distance, player_y + angle.sin() * distance);
+            println!("Apareció una salida en ({}, {}), a unas {:.0} celdas", exit.0, exit.1, distance);
+            
+            self.screamer_triggered = false;
+            self.next_endless_exit_time += self.rng.gen_range(ENDLESS_EXIT_INTERVAL.0..ENDLESS_EXIT_INTERVAL.1);
+        }
+    }
+    
+    /// Vacía la celda (x, y) después de que el jugador recogió lo que había en ella
+    pub fn take_pickup(&mut self, x: i32, y: i32) {
+        match &mut self.endless {
+            Some(world) => world.take_pickup(x, y),
+            None => {
+                if x >= 0 && y >= 0 && self.world_map.in_bounds(x as usize, y as usize) {
+                    self.world_map.set(x as usize, y as usize, Tile::Empty);
+                }
+            }
+        }
+    }
+    
+    // Centro de la celda de salida, si hay una
+    fn exit_point(&self) -> Option<(f32, f32)> {
+        match &self.endless {
+            Some(world) => world.exit().map(|(x, y)| (x as f32 + 0.5, y as f32 + 0.5)),
+            None => Some((self.exit_position.0 as f32 + 0.5, self.exit_position.1 as f32 + 0.5)),
+        }
+    }
+    
+    /// El jugador pisó la salida: pasa al siguiente nivel de la campaña, o gana si era
+    /// el último, un nivel de archivo o el modo infinito. Devuelve true si cambió de nivel.
+    pub fn reach_exit(&mut self) -> bool {
+        if self.endless.is_some() || self.loaded_level.is_some() || self.level_index + 1 >= CAMPAIGN.len() {
+            self.escaped = true;
+            return false;
+        }
+        
+        self.level_index += 1;
+        self.generate_level();
+        self.build_light_map();
+        println!("Entrando a {}", self.level_def().name);
+        
+        // Cada nivel empieza sin enemigo y con su screamer de salida disponible
+        self.restart_director();
+        self.screamer_triggered = false;
+        self.screamer_active = false;
+        self.level_transition_timer = LEVEL_TRANSITION_TIME;
+        true
+    }
+    
+    /// Usa siempre el generador indicado (o vuelve a los del nivel con None) y regenera el mapa
+    pub fn force_generator(&mut self, generator: Option<GeneratorKind>) {
+        self.forced_generator = generator;
+        if self.loaded_level.is_none() {
+            self.generate_level();
+        }
+    }
+    
+    fn generate_level(&mut self) {
+        let generator = &self.level_def().generator;
+        let scale = self.difficulty.settings().map_size;
+        self.generate_world(
+            (generator.width as f32 * scale).round() as usize,
+            (generator.height as f32 * scale).round() as usize,
+        );
+    }
+    
+    /// Cambia la dificultad y, si el mapa es generado, lo vuelve a generar con su tamaño
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        if difficulty == self.difficulty {
+            return;
+        }
+        self.difficulty = difficulty;
+        if self.loaded_level.is_none() {
+            self.generate_level();
+        }
+    }
+    
+    /// Enemigos que aparecen en el nivel actual con la dificultad elegida. Los que se
+    /// agregan repiten los tipos del nivel en orden.
+    pub fn enemy_roster(&self) -> Vec<&'static EnemyArchetype> {
+        let roster = self.level_def().enemy.roster;
+        if roster.is_empty() {
+            return Vec::new();
+        }
+        let count = (roster.len() as i32 + self.difficulty.settings().extra_enemies).max(1) as usize;
+        roster.iter().copied().cycle().take(count).collect()
+    }
+    
+    /// Anota el resultado de la partida, con su dificultad y semilla, en RESULTS_FILE
+    pub fn record_result(&self, escaped: bool) {
+        use std::io::Write;
+        
+        let line = format!(
+            "{} | dificultad: {} | semilla: {} | nivel: {} | tiempo: {:.1}s\n",
+            if escaped { "escapó" } else { "atrapado" },
+            self.difficulty.name(),
+            self.seed,
+            self.level_def().name,
+            self.game_timer,
+        );
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(RESULTS_FILE)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+        match result {
+            Ok(()) => println!("✓ Resultado guardado en {}", RESULTS_FILE),
+            Err(e) => println!("✗ No se pudo guardar el resultado en {}: {}", RESULTS_FILE, e),
+        }
+    }
+    
+    // Semilla del nivel actual: el nivel 0 usa la de la partida y los siguientes se
+    // derivan de ella, así una semilla repite la campaña completa
+    fn level_seed(&self) -> u64 {
+        self.seed.wrapping_add((self.level_index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+    
+    /// Reemplaza el mapa generado por un nivel hecho a mano
+    pub fn load_level(&mut self, level: Level) {
+        println!("Nivel cargado: {} ({}x{})", level.name, level.world_map.width(), level.world_map.height());
+        
+        self.world_map = level.world_map.clone();
+        self.exit_position = level.exit_position;
+        self.player_start = level.player_start;
+        self.enemy_activation_delay = level.enemy_delay;
+        self.enemy_spawn_points = level.enemy_spawn_points.clone();
+        self.level_name = level.name.clone();
+        
+        let start = (level.player_start.0 as usize, level.player_start.1 as usize);
+        self.exit_path_length = validate(&self.world_map, start, self.exit_position)
+            .exit_path_length
+            .unwrap_or(0);
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.lights = place_lights(&self.world_map, &self.level_def().generator, &mut self.rng);
+        self.loaded_level = Some(level);
+    }
+    
+    /// Nivel equivalente al mapa actual, para guardarlo como archivo
+    pub fn current_level(&self) -> Level {
+        Level {
+            name: self.level_name.clone(),
+            world_map: self.world_map.clone(),
+            player_start: self.player_start,
+            enemy_delay: self.enemy_activation_delay,
+            enemy_spawn_points: self.enemy_spawn_points.clone(),
+            exit_position: self.exit_position,
+        }
     }
     
     pub fn start_game(&mut self) {
         self.current_screen = Screen::Game;
-        self.enemy_activation_timer = 0.0;
+        self.paused = false;
+        // El modo infinito arma su mapa de luz a medida que carga chunks
+        if self.endless.is_none() {
+            self.build_light_map();
+        }
+        self.restart_director();
+    }
+    
+    // Ritmo desde cero para el nivel actual, con los enemigos todavía sin aparecer
+    fn restart_director(&mut self) {
+        let settings = self.difficulty.settings();
+        let delay = self.enemy_activation_delay.map(|delay| delay * settings.enemy_delay);
+        self.director = Director::new(delay, settings.scare_rate, self.level_seed());
         self.enemy_should_activate = false;
     }
     
+    /// Avanza el director y aplica lo que decide. Devuelve verdadero si empezó un screamer aleatorio.
+    pub fn update_director(&mut self, dt: f32, tension: &Tension) -> bool {
+        let mut scared = false;
+        for event in self.director.update(dt, tension) {
+            match event {
+                DirectorEvent::SpawnEnemies => {
+                    self.enemy_should_activate = true;
+                    println!("¡Enemigos activados a los {:.1} segundos!", self.game_timer);
+                }
+                // Nunca encima de otro screamer
+                DirectorEvent::Scare => {
+                    if !self.random_screamer_active && !self.screamer_active && !self.death_screamer_active {
+                        self.random_screamer_active = true;
+                        self.random_screamer_timer = 0.0;
+                        scared = true;
+                    }
+                }
+            }
+        }
+        scared
+    }
+    
     pub fn trigger_death(&mut self) {
+        self.record_result(false);
         self.game_over = true;
         self.death_screamer_active = true;
         self.death_screamer_timer = 0.0;
@@ -103,13 +450,9 @@ impl GameState {
             Screen::Game => {
                 if !self.game_over {
                     self.game_timer += dt;
-                    
-                    // Timer para activar el enemigo después de unos segundos
-                    self.enemy_activation_timer += dt;
-                    if self.enemy_activation_timer >= 10.0 && !self.enemy_should_activate {
-                        self.enemy_should_activate = true;
-                        println!("¡Enemigo activado después de 10 segundos!");
-                    }
+                    self.light_map.update(dt);
+                    self.level_transition_timer = (self.level_transition_timer - dt).max(0.0);
+                    self.enemy_alert_timer = (self.enemy_alert_timer - dt).max(0.0);
                     
                     // Actualizar screamer de salida
                     self.update_screamer(dt);
@@ -138,19 +481,12 @@ impl GameState {
     }
     
     pub fn update_random_screamer(&mut self, dt: f32) {
-        // Actualizar cooldown del screamer aleatorio
-        if self.random_screamer_cooldown > 0.0 {
-            self.random_screamer_cooldown -= dt;
-        }
-        
-        // Actualizar timer del screamer activo
+        // Actualizar timer del screamer activo; el director decide cuándo vuelve a haber otro
         if self.random_screamer_active {
             self.random_screamer_timer += dt;
             if self.random_screamer_timer >= 2.5 { // Duración ligeramente diferente
                 self.random_screamer_active = false;
                 self.random_screamer_timer = 0.0;
-                // Establecer un cooldown después del screamer
-                self.random_screamer_cooldown = 45.0; // 45 segundos de cooldown
             }
         }
     }
@@ -172,11 +508,13 @@ impl GameState {
     
     pub fn check_screamer_distance(&mut self, player_x: f32, player_y: f32) -> bool {
         if !self.screamer_triggered {
-            let exit_x = self.exit_position.0 as f32;
-            let exit_y = self.exit_position.1 as f32;
+            let Some((exit_x, exit_y)) = self.exit_point() else {
+                return false;
+            };
             let distance = ((player_x - exit_x).powi(2) + (player_y - exit_y).powi(2)).sqrt();
             
             if distance <= 3.0 {
+                self.director.on_scare();
                 self.screamer_triggered = true;
                 self.screamer_active = true;
                 self.screamer_timer = 0.0;
@@ -186,193 +524,130 @@ impl GameState {
         false
     }
     
-    pub fn check_random_screamer(&mut self) -> bool {
-        // Solo puede activarse si no hay cooldown y no está ya activo
-        if self.random_screamer_cooldown <= 0.0 && !self.random_screamer_active && 
-           !self.screamer_active && !self.death_screamer_active && 
-           self.game_timer > 20.0 && !self.game_over { // Esperar al menos 20 segundos
-            
-            // Probabilidad muy baja por frame (aproximadamente cada 30-60 segundos en promedio)
-            let mut rng = rand::thread_rng();
-            if rng.gen_bool(0.0001) { // 0.01% de probabilidad por frame
-                self.random_screamer_active = true;
-                self.random_screamer_timer = 0.0;
-                return true;
-            }
-        }
-        false
-    }
-    
-    fn generate_world(&mut self) {
-        let mut rng = rand::thread_rng();
+    /// Genera un mapa nuevo del tamaño indicado (mínimo MIN_MAP_SIZE por lado) con los
+    /// parámetros del nivel actual. El resultado depende solo de la semilla, el nivel y el tamaño.
+    pub fn generate_world(&mut self, width: usize, height: usize) {
+        let level_seed = self.level_seed();
+        let mut rng = StdRng::seed_from_u64(level_seed);
+        println!("Generando mapa {}x{} con semilla {}", width, height, level_seed);
+        self.level_name = format!("{} (semilla {})", self.level_def().name, self.seed);
+        self.player_start = (SPAWN_CELL.0 as f32 + 0.5, SPAWN_CELL.1 as f32 + 0.5, 0.0);
+        self.enemy_activation_delay = self.level_def().enemy.delay;
+        self.enemy_spawn_points.clear();
         
-        // Inicializar con paredes
-        for row in &mut self.world_map {
-            for cell in row {
-                *cell = 1;
+        // Cada nivel permite algunos generadores; la semilla decide cuál se usa
+        self.generator = self.forced_generator.unwrap_or_else(|| {
+            *self.level_def().generators.choose(&mut rng).unwrap_or(&GeneratorKind::Maze)
+        });
+        println!("Generador: {}", self.generator.name());
+        
+        let mut exit_path_length = None;
+        for attempt in 1..=MAX_GENERATION_ATTEMPTS {
+            self.build_layout(width, height, &mut rng);
+            exit_path_length = self.validate_layout();
+            if exit_path_length.is_some() {
+                break;
             }
+            println!("✗ Mapa inválido en el intento {}, regenerando...", attempt);
         }
         
-        // Crear laberinto usando algoritmo de generación mejorado
-        self.carve_maze(&mut rng);
+        // Si ningún intento sirvió, una sala abierta siempre conecta el inicio con la salida
+        let length = exit_path_length.unwrap_or_else(|| {
+            println!("✗ Sin mapa válido tras {} intentos: se usa una sala abierta", MAX_GENERATION_ATTEMPTS);
+            self.build_open_layout(width, height, &mut rng);
+            self.validate_layout().expect("una sala abierta siempre es un mapa válido")
+        });
+        self.exit_path_length = length;
+        println!("Camino más corto a la salida: {} pasos", length);
         
-        // Agregar algunas paredes con sangre aleatoriamente
-        self.add_bloody_walls(&mut rng);
-        
-        // Colocar la salida
-        self.place_exit(&mut rng);
+        // Lámparas del techo sobre el mapa ya terminado
+        self.lights = place_lights(&self.world_map, &self.level_def().generator, &mut rng);
         
-        // Asegurar que el punto de inicio esté libre (área más grande)
-        for y in 1..4 {
-            for x in 1..4 {
-                if y < 30 && x < 40 {
-                    self.world_map[y][x] = 0;
-                }
-            }
-        }
+        // El mismo RNG sigue decidiendo los eventos de la partida
+        self.rng = rng;
     }
     
-    fn carve_maze(&mut self, rng: &mut impl Rng) {
-        // Algoritmo mejorado de generación de laberinto para mapas grandes
-        let mut stack = Vec::new();
-        let mut visited = [[false; 40]; 30];
-        
-        let start_x = 1;
-        let start_y = 1;
-        
-        stack.push((start_x, start_y));
-        visited[start_y][start_x] = true;
-        self.world_map[start_y][start_x] = 0;
-        
-        while let Some((x, y)) = stack.pop() {
-            let neighbors = self.get_unvisited_neighbors(x, y, &visited);
-            
-            if !neighbors.is_empty() {
-                stack.push((x, y));
-                
-                let &(nx, ny) = neighbors.choose(rng).unwrap();
-                
-                // Carvar el camino hacia el vecino
-                let wall_x = (x + nx) / 2;
-                let wall_y = (y + ny) / 2;
-                
-                self.world_map[wall_y][wall_x] = 0;
-                self.world_map[ny][nx] = 0;
-                visited[ny][nx] = true;
-                
-                stack.push((nx, ny));
-            }
-        }
-        
-        // Crear pasillos adicionales para hacer el laberinto más interesante
-        for _ in 0..50 { // Más pasillos para el mapa grande
-            let x = rng.gen_range(1..39); // Ajustado para 40 columnas
-            let y = rng.gen_range(1..29); // Ajustado para 30 filas
-            if x % 2 == 1 && y % 2 == 1 {
-                self.world_map[y][x] = 0;
-                
-                // Conectar con un pasillo vecino ocasionalmente
-                if rng.gen_bool(0.3) {
-                    let directions = [(0, 2), (2, 0), (0, -2), (-2, 0)];
-                    if let Some(&(dx, dy)) = directions.choose(rng) {
-                        let new_x = x as i32 + dx;
-                        let new_y = y as i32 + dy;
-                        if new_x >= 1 && new_x < 39 && new_y >= 1 && new_y < 29 {
-                            let bridge_x = x as i32 + dx / 2;
-                            let bridge_y = y as i32 + dy / 2;
-                            self.world_map[bridge_y as usize][bridge_x as usize] = 0;
-                        }
-                    }
-                }
-            }
+    // Calcula hasta dónde llega cada lámpara del mapa fijo
+    fn build_light_map(&mut self) {
+        let (width, height) = (self.world_map.width(), self.world_map.height());
+        self.light_map = LightMap::build(&self.world_map, (0, 0), width, height, self.lights.clone());
+        println!("Luces del techo: {}", self.lights.len());
+    }
+    
+    // Valida que la salida y todas las áreas abiertas se alcancen desde el inicio,
+    // abriendo pasillos si hace falta. Devuelve los pasos del camino más corto a la salida.
+    fn validate_layout(&mut self) -> Option<usize> {
+        let mut report = validate(&self.world_map, SPAWN_CELL, self.exit_position);
+        if !report.is_valid() {
+            let corridors = connect_regions(&mut self.world_map, SPAWN_CELL);
+            println!("Mapa con zonas aisladas: {} pasillos abiertos para conectarlas", corridors);
+            report = validate(&self.world_map, SPAWN_CELL, self.exit_position);
         }
-        
-        // Crear algunas áreas abiertas para hacer el juego más interesante
-        self.create_open_areas(rng);
+        report.exit_path_length.filter(|_| report.is_valid())
     }
     
-    fn create_open_areas(&mut self, rng: &mut impl Rng) {
-        // Crear 3-5 áreas abiertas pequeñas en el mapa
-        let num_areas = rng.gen_range(3..6);
-        
-        for _ in 0..num_areas {
-            let center_x = rng.gen_range(5..35);
-            let center_y = rng.gen_range(5..25);
-            let size = rng.gen_range(2..4);
-            
-            // Crear área abierta
-            for dy in -(size as i32)..=(size as i32) {
-                for dx in -(size as i32)..=(size as i32) {
-                    let x = (center_x as i32 + dx) as usize;
-                    let y = (center_y as i32 + dy) as usize;
-                    
-                    if x < 40 && y < 30 {
-                        // Crear área circular
-                        let distance = (dx * dx + dy * dy) as f32;
-                        if distance <= (size * size) as f32 {
-                            self.world_map[y][x] = 0;
-                        }
-                    }
-                }
+    // Mapa de respaldo: todo el interior abierto, rodeado de paredes, con la salida lejos del inicio
+    fn build_open_layout(&mut self, width: usize, height: usize, rng: &mut StdRng) {
+        let width = width.max(MIN_MAP_SIZE);
+        let height = height.max(MIN_MAP_SIZE);
+        self.world_map = WorldMap::new(width, height, Tile::Wall);
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                self.world_map.set(x, y, Tile::Empty);
             }
         }
+        self.place_exit(rng);
     }
     
-    fn get_unvisited_neighbors(&self, x: usize, y: usize, visited: &[[bool; 40]; 30]) -> Vec<(usize, usize)> {
-        let mut neighbors = Vec::new();
-        let directions = [(0, 2), (2, 0), (0, -2), (-2, 0)];
+    fn build_layout(&mut self, width: usize, height: usize, rng: &mut StdRng) {
+        let width = width.max(MIN_MAP_SIZE);
+        let height = height.max(MIN_MAP_SIZE);
         
-        for &(dx, dy) in &directions {
-            let new_x = x as i32 + dx;
-            let new_y = y as i32 + dy;
-            
-            if new_x >= 1 && new_x < 39 && new_y >= 1 && new_y < 29 { // Ajustado para mapa 40x30
-                let nx = new_x as usize;
-                let ny = new_y as usize;
-                
-                if !visited[ny][nx] {
-                    neighbors.push((nx, ny));
-                }
-            }
+        // Inicializar con paredes
+        if self.world_map.width() == width && self.world_map.height() == height {
+            self.world_map.fill(Tile::Wall);
+        } else {
+            self.world_map = WorldMap::new(width, height, Tile::Wall);
         }
         
-        neighbors
-    }
-    
-    fn add_bloody_walls(&mut self, rng: &mut impl Rng) {
-        // Convertir algunas paredes normales en paredes con sangre
-        for y in 0..30 { // Ajustado para mapa 30 filas
-            for x in 0..40 { // Ajustado para mapa 40 columnas
-                if self.world_map[y][x] == 1 && rng.gen_bool(0.18) { // Ligeramente más probabilidad
-                    // Solo agregar sangre si hay al menos un espacio vacío adyacente
-                    let adjacent_empty = [
-                        (x.wrapping_sub(1), y),
-                        (x + 1, y),
-                        (x, y.wrapping_sub(1)),
-                        (x, y + 1),
-                    ].iter().any(|&(ax, ay)| {
-                        ax < 40 && ay < 30 && self.world_map[ay][ax] == 0 // Ajustado
-                    });
-                    
-                    if adjacent_empty {
-                        self.world_map[y][x] = 2; // Pared con sangre
-                    }
-                }
+        // Abrir los espacios transitables con el generador del nivel
+        let params = &self.level_def().generator;
+        self.generator.generator().carve(&mut self.world_map, params, rng);
+        
+        // Agregar algunas paredes con sangre aleatoriamente
+        add_bloody_walls(&mut self.world_map, params.bloody_wall_chance, rng);
+        
+        // Baterías para la linterna repartidas por los espacios abiertos
+        add_batteries(&mut self.world_map, params.battery_chance, rng);
+        
+        // Colocar la salida
+        self.place_exit(rng);
+        
+        // Asegurar que el punto de inicio esté libre (área más grande)
+        for y in 1..4 {
+            for x in 1..4 {
+                self.world_map.set(x, y, Tile::Empty);
             }
         }
     }
     
+    
     fn place_exit(&mut self, rng: &mut impl Rng) {
+        let width = self.world_map.width();
+        let height = self.world_map.height();
+        
         // Encontrar posiciones vacías lejas del inicio
         let mut far_positions = Vec::new();
         let start_x = 2.0;
         let start_y = 2.0;
+        // 15 celdas en el mapa original de 40 columnas
+        let min_distance = width.max(height) as f32 * 0.375;
         
-        for y in 1..29 { // Ajustado para mapa 30 filas
-            for x in 1..39 { // Ajustado para mapa 40 columnas
-                if self.world_map[y][x] == 0 {
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                if self.world_map.get(x, y) == Some(Tile::Empty) {
                     let distance = ((x as f32 - start_x).powi(2) + (y as f32 - start_y).powi(2)).sqrt();
-                    if distance > 15.0 { // Distancia mínima mayor para mapa grande
+                    if distance > min_distance {
                         far_positions.push((x, y));
                     }
                 }
@@ -381,11 +656,13 @@ impl GameState {
         
         // Colocar la salida en una posición lejana aleatoria
         if let Some(&(exit_x, exit_y)) = far_positions.choose(rng) {
-            self.world_map[exit_y][exit_x] = 3; // Salida
+            self.world_map.set(exit_x, exit_y, Tile::Exit); // Salida
             self.exit_position = (exit_x, exit_y);
         } else {
             // Fallback: colocar en una esquina lejana
-            self.world_map[28][37] = 3; // Ajustado para mapa 40x30
-            self.exit_position = (37, 28);
+            let (exit_x, exit_y) = (width - 3, height - 2);
+            self.world_map.set(exit_x, exit_y, Tile::Exit);
+            self.exit_position = (exit_x, exit_y);
         }
-    }}
\ No newline at end of file
+    }
+}
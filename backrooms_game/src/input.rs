@@ -0,0 +1,759 @@
+// Capa de acciones entre los dispositivos y el juego: el jugador y los menús preguntan
+// por acciones (avanzar, correr, pausar...) y no por teclas. Cada acción tiene una o
+// más teclas y, opcionalmente, un botón del control; se guardan en un archivo de texto
+// con el formato de "clave: valor" de los niveles, una acción por línea:
+//
+//     # Controles de Backrooms
+//     move_forward: W
+//     sprint: LeftShift, RightShift, PadLeftStick
+//
+// Los nombres de tecla salen de la tabla KEYS y los botones llevan "Pad" adelante. Con la feature "gamepad" también se lee el primer control que se use: el
+// stick izquierdo mueve y el derecho mira.
+
+use macroquad::prelude::*;
+use std::collections::HashSet;
+#[cfg(feature = "gamepad")]
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+
+pub const BINDINGS_FILE: &str = "controls.txt";
+
+// Zona muerta de los sticks
+const STICK_DEADZONE: f32 = 0.2;
+// Con el stick derecho a fondo la vista gira como si el mouse se moviera estos pixeles por segundo
+const STICK_LOOK_SPEED: f32 = 1800.0;
+/// Teclas que puede tener cada acción
+pub const MAX_KEYS: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    Sprint,
+    Crouch,
+    Flashlight,
+    /// Empezar desde el menú y seguir en las pantallas de fin de partida
+    Interact,
+    /// Pausar la partida y volver atrás en los menús
+    Pause,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Endless,
+    Options,
+    /// Volver a los controles por defecto en la pantalla de controles
+    ResetBindings,
+    /// Mostrar en el minimapa el camino y el estado de cada enemigo
+    DebugMinimap,
+}
+
+impl Action {
+    pub const ALL: [Action; 19] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Sprint,
+        Action::Crouch,
+        Action::Flashlight,
+        Action::Interact,
+        Action::Pause,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuLeft,
+        Action::MenuRight,
+        Action::Endless,
+        Action::Options,
+        Action::ResetBindings,
+        Action::DebugMinimap,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move forward",
+            Action::MoveBackward => "Move backward",
+            Action::StrafeLeft => "Strafe left",
+            Action::StrafeRight => "Strafe right",
+            Action::TurnLeft => "Turn left",
+            Action::TurnRight => "Turn right",
+            Action::Sprint => "Sprint",
+            Action::Crouch => "Crouch",
+            Action::Flashlight => "Flashlight",
+            Action::Interact => "Interact / Start",
+            Action::Pause => "Pause / Back",
+            Action::MenuUp => "Menu up",
+            Action::MenuDown => "Menu down",
+            Action::MenuLeft => "Menu left",
+            Action::MenuRight => "Menu right",
+            Action::Endless => "Endless mode",
+            Action::Options => "Options",
+            Action::ResetBindings => "Reset controls",
+            Action::DebugMinimap => "Debug minimap",
+        }
+    }
+
+    // Clave en el archivo de controles
+    fn key(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBackward => "move_backward",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::Sprint => "sprint",
+            Action::Crouch => "crouch",
+            Action::Flashlight => "flashlight",
+            Action::Interact => "interact",
+            Action::Pause => "pause",
+            Action::MenuUp => "menu_up",
+            Action::MenuDown => "menu_down",
+            Action::MenuLeft => "menu_left",
+            Action::MenuRight => "menu_right",
+            Action::Endless => "endless",
+            Action::Options => "options",
+            Action::ResetBindings => "reset_bindings",
+            Action::DebugMinimap => "debug_minimap",
+        }
+    }
+
+    fn index(self) -> usize {
+        Action::ALL.iter().position(|&action| action == self).unwrap_or(0)
+    }
+
+    // Se usa durante la partida
+    fn in_game(self) -> bool {
+        !self.in_menus() || self == Action::Pause
+    }
+
+    // Se usa en el menú y las demás pantallas fuera de la partida
+    fn in_menus(self) -> bool {
+        matches!(
+            self,
+            Action::Interact
+                | Action::Pause
+                | Action::MenuUp
+                | Action::MenuDown
+                | Action::MenuLeft
+                | Action::MenuRight
+                | Action::Endless
+                | Action::Options
+                | Action::ResetBindings
+        )
+    }
+
+    /// Dos acciones que se usan en la misma pantalla no pueden compartir tecla ni botón
+    fn conflicts_with(self, other: Action) -> bool {
+        self != other && ((self.in_game() && other.in_game()) || (self.in_menus() && other.in_menus()))
+    }
+
+    /// Sin estas no se puede salir de la pausa ni avanzar por los menús: nunca se quedan sin controles
+    fn is_essential(self) -> bool {
+        matches!(self, Action::Pause | Action::Interact)
+    }
+}
+
+/// Botones del control, con nombres de control de Xbox para los gatillos y bumpers
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl PadButton {
+    pub const ALL: [PadButton; 16] = [
+        PadButton::South,
+        PadButton::East,
+        PadButton::North,
+        PadButton::West,
+        PadButton::LeftBumper,
+        PadButton::RightBumper,
+        PadButton::LeftTrigger,
+        PadButton::RightTrigger,
+        PadButton::Select,
+        PadButton::Start,
+        PadButton::LeftStick,
+        PadButton::RightStick,
+        PadButton::DPadUp,
+        PadButton::DPadDown,
+        PadButton::DPadLeft,
+        PadButton::DPadRight,
+    ];
+
+    /// Nombre en el archivo de controles y en pantalla
+    pub fn name(self) -> String {
+        format!("Pad{:?}", self)
+    }
+}
+
+// Teclas que se pueden asignar, con su nombre en el archivo de controles y en pantalla
+const KEYS: [(KeyCode, &str); 120] = [
+    (KeyCode::Space, "Space"), (KeyCode::Apostrophe, "Apostrophe"), (KeyCode::Comma, "Comma"),
+    (KeyCode::Minus, "Minus"), (KeyCode::Period, "Period"), (KeyCode::Slash, "Slash"),
+    (KeyCode::Key0, "Key0"), (KeyCode::Key1, "Key1"), (KeyCode::Key2, "Key2"),
+    (KeyCode::Key3, "Key3"), (KeyCode::Key4, "Key4"), (KeyCode::Key5, "Key5"),
+    (KeyCode::Key6, "Key6"), (KeyCode::Key7, "Key7"), (KeyCode::Key8, "Key8"),
+    (KeyCode::Key9, "Key9"), (KeyCode::Semicolon, "Semicolon"), (KeyCode::Equal, "Equal"),
+    (KeyCode::A, "A"), (KeyCode::B, "B"), (KeyCode::C, "C"), (KeyCode::D, "D"), (KeyCode::E, "E"),
+    (KeyCode::F, "F"), (KeyCode::G, "G"), (KeyCode::H, "H"), (KeyCode::I, "I"), (KeyCode::J, "J"),
+    (KeyCode::K, "K"), (KeyCode::L, "L"), (KeyCode::M, "M"), (KeyCode::N, "N"), (KeyCode::O, "O"),
+    (KeyCode::P, "P"), (KeyCode::Q, "Q"), (KeyCode::R, "R"), (KeyCode::S, "S"), (KeyCode::T, "T"),
+    (KeyCode::U, "U"), (KeyCode::V, "V"), (KeyCode::W, "W"), (KeyCode::X, "X"), (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"), (KeyCode::LeftBracket, "LeftBracket"), (KeyCode::Backslash, "Backslash"),
+    (KeyCode::RightBracket, "RightBracket"), (KeyCode::GraveAccent, "GraveAccent"),
+    (KeyCode::World1, "World1"), (KeyCode::World2, "World2"), (KeyCode::Escape, "Escape"),
+    (KeyCode::Enter, "Enter"), (KeyCode::Tab, "Tab"), (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Insert, "Insert"), (KeyCode::Delete, "Delete"), (KeyCode::Right, "Right"),
+    (KeyCode::Left, "Left"), (KeyCode::Down, "Down"), (KeyCode::Up, "Up"),
+    (KeyCode::PageUp, "PageUp"), (KeyCode::PageDown, "PageDown"), (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"), (KeyCode::CapsLock, "CapsLock"), (KeyCode::ScrollLock, "ScrollLock"),
+    (KeyCode::NumLock, "NumLock"), (KeyCode::PrintScreen, "PrintScreen"), (KeyCode::Pause, "Pause"),
+    (KeyCode::F1, "F1"), (KeyCode::F2, "F2"), (KeyCode::F3, "F3"), (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"), (KeyCode::F6, "F6"), (KeyCode::F7, "F7"), (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"), (KeyCode::F10, "F10"), (KeyCode::F11, "F11"), (KeyCode::F12, "F12"),
+    (KeyCode::F13, "F13"), (KeyCode::F14, "F14"), (KeyCode::F15, "F15"), (KeyCode::F16, "F16"),
+    (KeyCode::F17, "F17"), (KeyCode::F18, "F18"), (KeyCode::F19, "F19"), (KeyCode::F20, "F20"),
+    (KeyCode::F21, "F21"), (KeyCode::F22, "F22"), (KeyCode::F23, "F23"), (KeyCode::F24, "F24"),
+    (KeyCode::F25, "F25"), (KeyCode::Kp0, "Kp0"), (KeyCode::Kp1, "Kp1"), (KeyCode::Kp2, "Kp2"),
+    (KeyCode::Kp3, "Kp3"), (KeyCode::Kp4, "Kp4"), (KeyCode::Kp5, "Kp5"), (KeyCode::Kp6, "Kp6"),
+    (KeyCode::Kp7, "Kp7"), (KeyCode::Kp8, "Kp8"), (KeyCode::Kp9, "Kp9"),
+    (KeyCode::KpDecimal, "KpDecimal"), (KeyCode::KpDivide, "KpDivide"),
+    (KeyCode::KpMultiply, "KpMultiply"), (KeyCode::KpSubtract, "KpSubtract"),
+    (KeyCode::KpAdd, "KpAdd"), (KeyCode::KpEnter, "KpEnter"), (KeyCode::KpEqual, "KpEqual"),
+    (KeyCode::LeftShift, "LeftShift"), (KeyCode::LeftControl, "LeftControl"),
+    (KeyCode::LeftAlt, "LeftAlt"), (KeyCode::LeftSuper, "LeftSuper"),
+    (KeyCode::RightShift, "RightShift"), (KeyCode::RightControl, "RightControl"),
+    (KeyCode::RightAlt, "RightAlt"), (KeyCode::RightSuper, "RightSuper"), (KeyCode::Menu, "Menu"),
+];
+
+/// Verdadero si la tecla se puede asignar a una acción (y guardar en el archivo)
+pub fn is_bindable(key: KeyCode) -> bool {
+    KEYS.iter().any(|&(bindable, _)| bindable == key)
+}
+
+fn key_name(key: KeyCode) -> &'static str {
+    KEYS.iter().find(|&&(bindable, _)| bindable == key).map_or("?", |&(_, name)| name)
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEYS.iter().find(|&&(_, key_name)| key_name == name).map(|&(key, _)| key)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub keys: Vec<KeyCode>,
+    pub button: Option<PadButton>,
+}
+
+impl Binding {
+    fn new(keys: &[KeyCode], button: Option<PadButton>) -> Self {
+        Self { keys: keys.to_vec(), button }
+    }
+
+    /// Teclas y botón como se muestran en pantalla y se guardan en el archivo
+    pub fn text(&self) -> String {
+        let mut names: Vec<String> = self.keys.iter().map(|&key| String::from(key_name(key))).collect();
+        names.extend(self.button.map(PadButton::name));
+        names.join(", ")
+    }
+
+    /// Nombre de la tecla en el lugar `slot`, si tiene una
+    pub fn key_text(&self, slot: usize) -> Option<&'static str> {
+        self.keys.get(slot).map(|&key| key_name(key))
+    }
+
+    /// Solo la primera tecla (o el botón si no tiene teclas), para las ayudas en pantalla
+    pub fn primary(&self) -> String {
+        match (self.keys.first(), self.button) {
+            (Some(&key), _) => String::from(key_name(key)),
+            (None, Some(button)) => button.name(),
+            (None, None) => String::from("-"),
+        }
+    }
+}
+
+/// Teclas y botones de cada acción, en el orden de Action::ALL
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    bindings: Vec<Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|action| match action {
+                Action::MoveForward => Binding::new(&[KeyCode::W], None),
+                Action::MoveBackward => Binding::new(&[KeyCode::S], None),
+                Action::StrafeLeft => Binding::new(&[KeyCode::A], None),
+                Action::StrafeRight => Binding::new(&[KeyCode::D], None),
+                Action::TurnLeft => Binding::new(&[KeyCode::Left], None),
+                Action::TurnRight => Binding::new(&[KeyCode::Right], None),
+                Action::Sprint => Binding::new(&[KeyCode::LeftShift, KeyCode::RightShift], Some(PadButton::LeftStick)),
+                Action::Crouch => Binding::new(&[KeyCode::C, KeyCode::LeftControl], Some(PadButton::East)),
+                Action::Flashlight => Binding::new(&[KeyCode::F], Some(PadButton::North)),
+                Action::Interact => Binding::new(&[KeyCode::Space, KeyCode::Enter], Some(PadButton::South)),
+                Action::Pause => Binding::new(&[KeyCode::Escape], Some(PadButton::Start)),
+                Action::MenuUp => Binding::new(&[KeyCode::Up], Some(PadButton::DPadUp)),
+                Action::MenuDown => Binding::new(&[KeyCode::Down], Some(PadButton::DPadDown)),
+                Action::MenuLeft => Binding::new(&[KeyCode::Left], Some(PadButton::DPadLeft)),
+                Action::MenuRight => Binding::new(&[KeyCode::Right], Some(PadButton::DPadRight)),
+                Action::Endless => Binding::new(&[KeyCode::E], Some(PadButton::West)),
+                Action::Options => Binding::new(&[KeyCode::O], Some(PadButton::Select)),
+                Action::ResetBindings => Binding::new(&[KeyCode::Backspace], None),
+                Action::DebugMinimap => Binding::new(&[KeyCode::F3], None),
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Bindings {
+    /// Lee los controles guardados; si no hay archivo usa los de por defecto
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => parse_bindings(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, serialize_bindings(self))
+    }
+
+    pub fn get(&self, action: Action) -> &Binding {
+        &self.bindings[action.index()]
+    }
+
+    /// Pone `key` en el lugar `slot` de la acción, o la agrega al final si el lugar está
+    /// libre; las demás teclas y el botón no cambian. Si otra acción de la misma pantalla
+    /// usaba esa tecla, se queda con la que tenía este lugar. Devuelve Err con esa acción
+    /// si se quedaría sin teclas y es una de las que no pueden quedarse sin controles.
+    pub fn bind_key(&mut self, action: Action, slot: usize, key: KeyCode) -> Result<(), Action> {
+        let keys = &self.bindings[action.index()].keys;
+        let previous = keys.get(slot).copied();
+        if keys.contains(&key) || (previous.is_none() && keys.len() >= MAX_KEYS) {
+            return Ok(());
+        }
+
+        if let Some(other) = self.conflict(action, |binding| binding.keys.contains(&key)) {
+            let other_keys = &mut self.bindings[other.index()].keys;
+            match previous {
+                Some(previous) if !other_keys.contains(&previous) => {
+                    other_keys.iter_mut().filter(|bound| **bound == key).for_each(|bound| *bound = previous);
+                }
+                _ if other.is_essential() && other_keys.len() == 1 => return Err(other),
+                _ => other_keys.retain(|&bound| bound != key),
+            }
+        }
+
+        let keys = &mut self.bindings[action.index()].keys;
+        if previous.is_some() {
+            keys[slot] = key;
+        } else {
+            keys.push(key);
+        }
+        Ok(())
+    }
+
+    /// Pone el botón del control de la acción. Como con las teclas, si otra acción de la
+    /// misma pantalla lo usaba se queda con el botón anterior de esta.
+    pub fn bind_button(&mut self, action: Action, button: PadButton) -> Result<(), Action> {
+        let previous = self.bindings[action.index()].button;
+        if previous == Some(button) {
+            return Ok(());
+        }
+
+        if let Some(other) = self.conflict(action, |binding| binding.button == Some(button)) {
+            if previous.is_none() && other.is_essential() {
+                return Err(other);
+            }
+            self.bindings[other.index()].button = previous;
+        }
+
+        self.bindings[action.index()].button = Some(button);
+        Ok(())
+    }
+
+    // Acción de la misma pantalla que `action` cuyos controles cumplen `uses`
+    fn conflict(&self, action: Action, uses: impl Fn(&Binding) -> bool) -> Option<Action> {
+        Action::ALL.into_iter().find(|&other| action.conflicts_with(other) && uses(self.get(other)))
+    }
+}
+
+pub fn parse_bindings(text: &str) -> Bindings {
+    let mut bindings = Bindings::default();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            println!("✗ Controles, línea {}: se esperaba \"clave: valor\"", index + 1);
+            continue;
+        };
+        let key = key.trim();
+        let Some(action) = Action::ALL.into_iter().find(|action| action.key() == key) else {
+            println!("✗ Controles, línea {}: acción desconocida \"{}\"", index + 1, key);
+            continue;
+        };
+
+        // Una línea sin nada que se entienda deja la acción con sus controles por defecto
+        let mut binding = Binding::new(&[], None);
+        for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if let Some(button) = PadButton::ALL.into_iter().find(|button| button.name() == name) {
+                binding.button = Some(button);
+            } else if let Some(key) = key_from_name(name) {
+                binding.keys.push(key);
+            } else {
+                println!("✗ Controles, línea {}: tecla o botón desconocido \"{}\"", index + 1, name);
+            }
+        }
+        if !binding.keys.is_empty() || binding.button.is_some() {
+            bindings.bindings[action.index()] = binding;
+        }
+    }
+
+    bindings
+}
+
+pub fn serialize_bindings(bindings: &Bindings) -> String {
+    let mut text = String::from("# Controles de Backrooms\n");
+    for action in Action::ALL {
+        text.push_str(&format!("{}: {}\n", action.key(), bindings.get(action).text()));
+    }
+    text
+}
+
+// Stick sin la zona muerta, reescalado para que empiece en 0 justo al salir de ella
+fn apply_deadzone(stick: Vec2) -> Vec2 {
+    let length = stick.length();
+    if length < STICK_DEADZONE {
+        return Vec2::ZERO;
+    }
+    stick / length * ((length - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0)
+}
+
+#[cfg(feature = "gamepad")]
+struct Gamepads {
+    gilrs: Gilrs,
+    // Último control que mandó algo; es el que se lee
+    active: Option<GamepadId>,
+}
+
+/// Estado de las acciones en el cuadro actual; se actualiza una vez por cuadro
+pub struct Input {
+    pub bindings: Bindings,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<Gamepads>,
+    // Botones del control apretados, y los que se apretaron en este cuadro
+    pad_down: HashSet<PadButton>,
+    pad_pressed: HashSet<PadButton>,
+    // Sticks tal como llegan del control, de -1 a 1 con y hacia arriba
+    left_stick: Vec2,
+    right_stick: Vec2,
+    // Movimiento del mouse en este cuadro, en pixeles
+    mouse_delta: Vec2,
+}
+
+impl Input {
+    /// Solo teclado y mouse hasta que se llame a enable_gamepads
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            #[cfg(feature = "gamepad")]
+            gamepads: None,
+            pad_down: HashSet::new(),
+            pad_pressed: HashSet::new(),
+            left_stick: Vec2::ZERO,
+            right_stick: Vec2::ZERO,
+            mouse_delta: Vec2::ZERO,
+        }
+    }
+
+    /// Empieza a leer los controles conectados; sin la feature "gamepad" no hace nada
+    pub fn enable_gamepads(&mut self) {
+        #[cfg(feature = "gamepad")]
+        match Gilrs::new() {
+            Ok(gilrs) => {
+                println!("✓ Controles: {} conectado(s)", gilrs.gamepads().count());
+                self.gamepads = Some(Gamepads { gilrs, active: None });
+            }
+            Err(e) => println!("✗ No se pudieron leer los controles: {}", e),
+        }
+    }
+
+    pub fn update(&mut self) {
+        // Movimiento crudo desde el último cuadro; con el cursor capturado macroquad lo
+        // acumula sin topes de pantalla
+        self.mouse_delta = -mouse_delta_position() * vec2(screen_width(), screen_height()) / 2.0;
+        self.pad_pressed.clear();
+        #[cfg(feature = "gamepad")]
+        self.poll_gamepads();
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepads(&mut self) {
+        let Some(gamepads) = &mut self.gamepads else {
+            return;
+        };
+        while let Some(event) = gamepads.gilrs.next_event() {
+            gamepads.active = Some(event.id);
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(button) = pad_button(button) {
+                        self.pad_down.insert(button);
+                        self.pad_pressed.insert(button);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(button) = pad_button(button) {
+                        self.pad_down.remove(&button);
+                    }
+                }
+                EventType::Disconnected => {
+                    gamepads.active = None;
+                    self.pad_down.clear();
+                }
+                _ => {}
+            }
+        }
+
+        (self.left_stick, self.right_stick) = match gamepads.active {
+            Some(id) => {
+                let gamepad = gamepads.gilrs.gamepad(id);
+                let stick = |x, y| vec2(gamepad.value(x), gamepad.value(y));
+                (stick(Axis::LeftStickX, Axis::LeftStickY), stick(Axis::RightStickX, Axis::RightStickY))
+            }
+            None => (Vec2::ZERO, Vec2::ZERO),
+        };
+    }
+
+    /// Verdadero mientras alguna tecla o el botón de la acción esté apretado
+    pub fn down(&self, action: Action) -> bool {
+        let binding = self.bindings.get(action);
+        binding.keys.iter().any(|&key| is_key_down(key)) || binding.button.is_some_and(|button| self.pad_down.contains(&button))
+    }
+
+    /// Verdadero solo en el cuadro en que se apretó
+    pub fn pressed(&self, action: Action) -> bool {
+        let binding = self.bindings.get(action);
+        binding.keys.iter().any(|&key| is_key_pressed(key)) || binding.button.is_some_and(|button| self.pad_pressed.contains(&button))
+    }
+
+    /// Botón del control apretado en este cuadro, si hubo alguno
+    pub fn last_pad_pressed(&self) -> Option<PadButton> {
+        self.pad_pressed.iter().next().copied()
+    }
+
+    /// Hacia dónde quiere moverse: x a la derecha, y hacia adelante, de largo hasta 1
+    pub fn movement(&self) -> Vec2 {
+        let axis = |negative, positive| self.down(positive) as i32 as f32 - self.down(negative) as i32 as f32;
+        let keys = vec2(axis(Action::StrafeLeft, Action::StrafeRight), axis(Action::MoveBackward, Action::MoveForward));
+        (keys + apply_deadzone(self.left_stick)).clamp_length_max(1.0)
+    }
+
+    /// Giro con teclas: -1 a la izquierda, 1 a la derecha
+    pub fn turn(&self) -> f32 {
+        self.down(Action::TurnRight) as i32 as f32 - self.down(Action::TurnLeft) as i32 as f32
+    }
+
+    /// Cuánto mirar en este cuadro, en pixeles de mouse (y positivo = hacia abajo): el
+    /// mouse más el stick derecho
+    pub fn look_delta(&self, dt: f32) -> Vec2 {
+        let stick = apply_deadzone(self.right_stick);
+        self.mouse_delta + vec2(stick.x, -stick.y) * STICK_LOOK_SPEED * dt
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn pad_button(button: Button) -> Option<PadButton> {
+    Some(match button {
+        Button::South => PadButton::South,
+        Button::East => PadButton::East,
+        Button::North => PadButton::North,
+        Button::West => PadButton::West,
+        Button::LeftTrigger => PadButton::LeftBumper,
+        Button::RightTrigger => PadButton::RightBumper,
+        Button::LeftTrigger2 => PadButton::LeftTrigger,
+        Button::RightTrigger2 => PadButton::RightTrigger,
+        Button::Select => PadButton::Select,
+        Button::Start => PadButton::Start,
+        Button::LeftThumb => PadButton::LeftStick,
+        Button::RightThumb => PadButton::RightStick,
+        Button::DPadUp => PadButton::DPadUp,
+        Button::DPadDown => PadButton::DPadDown,
+        Button::DPadLeft => PadButton::DPadLeft,
+        Button::DPadRight => PadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip_and_keep_defaults_for_bad_lines() {
+        let mut bindings = Bindings::default();
+        assert_eq!(bindings.bind_key(Action::MoveForward, 1, KeyCode::Up), Ok(()));
+        assert_eq!(bindings.bind_button(Action::Flashlight, PadButton::RightBumper), Ok(()));
+        assert_eq!(parse_bindings(&serialize_bindings(&bindings)), bindings);
+
+        let parsed = parse_bindings("sprint: Q, PadWest\njump: Space\ncrouch: Nada\nbasura\n");
+        assert_eq!(parsed.get(Action::Sprint), &Binding::new(&[KeyCode::Q], Some(PadButton::West)));
+        assert_eq!(parsed.get(Action::Crouch), Bindings::default().get(Action::Crouch));
+    }
+
+    #[test]
+    fn sticks_ignore_the_deadzone() {
+        assert_eq!(apply_deadzone(vec2(0.1, -0.1)), Vec2::ZERO);
+        assert!(apply_deadzone(vec2(STICK_DEADZONE + 0.01, 0.0)).x < 0.05);
+        assert!((apply_deadzone(vec2(0.0, -1.0)) - vec2(0.0, -1.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn binding_a_key_replaces_one_slot_and_swaps_conflicts() {
+        let mut bindings = Bindings::default();
+
+        // Lugar libre: se agrega al lado de la que ya tenía
+        assert_eq!(bindings.bind_key(Action::MoveForward, 1, KeyCode::I), Ok(()));
+        assert_eq!(bindings.get(Action::MoveForward).keys, vec![KeyCode::W, KeyCode::I]);
+
+        // La S era de MoveBackward: se intercambian
+        assert_eq!(bindings.bind_key(Action::MoveForward, 0, KeyCode::S), Ok(()));
+        assert_eq!(bindings.get(Action::MoveForward).keys, vec![KeyCode::S, KeyCode::I]);
+        assert_eq!(bindings.get(Action::MoveBackward).keys, vec![KeyCode::W]);
+
+        // Acciones de pantallas distintas pueden compartir tecla
+        assert_eq!(bindings.bind_key(Action::Flashlight, 0, KeyCode::Up), Ok(()));
+        assert_eq!(bindings.get(Action::MenuUp).keys, vec![KeyCode::Up]);
+
+        // Sin lugar que intercambiar, la pausa no puede perder su única tecla
+        assert_eq!(bindings.bind_key(Action::Crouch, 2, KeyCode::Escape), Err(Action::Pause));
+        assert_eq!(bindings.get(Action::Pause).keys, vec![KeyCode::Escape]);
+        assert_eq!(bindings.get(Action::Crouch).keys, vec![KeyCode::C, KeyCode::LeftControl]);
+
+        // Ni más teclas de las que entran
+        assert_eq!(bindings.bind_key(Action::Crouch, 2, KeyCode::V), Ok(()));
+        assert_eq!(bindings.bind_key(Action::Crouch, 3, KeyCode::B), Ok(()));
+        assert_eq!(bindings.get(Action::Crouch).keys.len(), MAX_KEYS);
+    }
+
+    #[test]
+    fn binding_a_button_swaps_conflicts() {
+        let mut bindings = Bindings::default();
+        assert_eq!(bindings.bind_button(Action::Flashlight, PadButton::East), Ok(()));
+        assert_eq!(bindings.get(Action::Flashlight).button, Some(PadButton::East));
+        assert_eq!(bindings.get(Action::Crouch).button, Some(PadButton::North));
+
+        // Sin botón que darle a cambio, Interact no puede perder el suyo
+        assert_eq!(bindings.bind_button(Action::ResetBindings, PadButton::South), Err(Action::Interact));
+        assert_eq!(bindings.get(Action::Interact).button, Some(PadButton::South));
+        assert_eq!(bindings.bind_button(Action::Endless, PadButton::South), Ok(()));
+        assert_eq!(bindings.get(Action::Interact).button, Some(PadButton::West));
+    }
+
+    #[test]
+    fn default_bindings_have_no_conflicts() {
+        let bindings = Bindings::default();
+        for action in Action::ALL {
+            let binding = bindings.get(action);
+            assert!(!binding.keys.is_empty(), "{} sin teclas", action.name());
+            for &key in &binding.keys {
+                assert_eq!(bindings.conflict(action, |other| other.keys.contains(&key)), None, "{}", key_name(key));
+            }
+            if let Some(button) = binding.button {
+                assert_eq!(bindings.conflict(action, |other| other.button == Some(button)), None, "{}", button.name());
+            }
+        }
+    }
+
+    #[test]
+    fn every_bindable_key_has_a_unique_name() {
+        for (index, &(key, name)) in KEYS.iter().enumerate() {
+            assert_eq!(key_from_name(name), Some(key));
+            assert!(KEYS[..index].iter().all(|&(other, _)| other != key), "{} repetida", name);
+        }
+    }
+
+    // Controles sin teclas (leerlas necesita una ventana): las acciones de movimiento van
+    // en los botones de la cruceta
+    fn pad_input() -> Input {
+        let mut bindings = Bindings::default();
+        for binding in &mut bindings.bindings {
+            binding.keys.clear();
+        }
+        for (action, button) in [
+            (Action::MoveForward, PadButton::DPadUp),
+            (Action::MoveBackward, PadButton::DPadDown),
+            (Action::StrafeLeft, PadButton::DPadLeft),
+            (Action::StrafeRight, PadButton::DPadRight),
+        ] {
+            bindings.bindings[action.index()].button = Some(button);
+        }
+        Input::new(bindings)
+    }
+
+    #[test]
+    fn movement_combines_buttons_and_stick() {
+        let mut input = pad_input();
+        assert_eq!(input.movement(), Vec2::ZERO);
+
+        input.pad_down.insert(PadButton::DPadUp);
+        assert_eq!(input.movement(), vec2(0.0, 1.0));
+
+        // Adelante y a la derecha a la vez no es más rápido que en una sola dirección
+        input.pad_down.insert(PadButton::DPadRight);
+        assert!((input.movement().length() - 1.0).abs() < 1e-5);
+
+        // Atrás y adelante se anulan; el stick dentro de la zona muerta no mueve
+        input.pad_down.clear();
+        input.pad_down.insert(PadButton::DPadUp);
+        input.pad_down.insert(PadButton::DPadDown);
+        input.left_stick = vec2(0.1, 0.1);
+        assert_eq!(input.movement(), Vec2::ZERO);
+
+        input.left_stick = vec2(-1.0, 0.0);
+        assert!((input.movement() - vec2(-1.0, 0.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn look_delta_adds_mouse_and_right_stick() {
+        let mut input = pad_input();
+        input.mouse_delta = vec2(3.0, -2.0);
+        assert_eq!(input.look_delta(1.0 / 60.0), vec2(3.0, -2.0));
+
+        // Stick hacia arriba mira hacia arriba (y negativo), proporcional al tiempo
+        input.mouse_delta = Vec2::ZERO;
+        input.right_stick = vec2(0.0, 1.0);
+        let half = input.look_delta(0.5);
+        assert!((half - vec2(0.0, -STICK_LOOK_SPEED * 0.5)).length() < 1e-2);
+        assert!((input.look_delta(1.0) - half * 2.0).length() < 1e-2);
+
+        input.right_stick = vec2(0.15, -0.1);
+        assert_eq!(input.look_delta(1.0), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,323 @@
+// Formato de texto para niveles hechos a mano.
+//
+//     # Las líneas con # al inicio del encabezado son comentarios
+//     name: Oficina 27
+//     player_start: 2.5 2.5 0.0
+//     enemy_delay: 10
+//     map:
+//     ########
+//     #P..%..#
+//     #..E..X#
+//     ########
+//
+// Encabezado (todas las claves son opcionales):
+//   name          nombre del nivel
+//   player_start  posición "x y" y opcionalmente el ángulo; si falta se usa la P del mapa
+//   enemy_delay   segundos antes de que aparezca el enemigo, o "never" para no tener enemigo
+//
+// Después de "map:" cada fila es una fila del mapa. Los símbolos de celda salen de
+// la tabla de tiles (. vacío, # pared, % pared con sangre, X salida, B batería). Además:
+//   P  inicio del jugador (celda vacía)
+//   E  punto de aparición del enemigo (celda vacía); sin ninguna, aparece al azar
+
+use std::fmt;
+use crate::game_state::DEFAULT_ENEMY_DELAY;
+use crate::map_validation::validate;
+use crate::tile::Tile;
+use crate::world_map::WorldMap;
+
+const PLAYER_MARKER: char = 'P';
+const ENEMY_MARKER: char = 'E';
+
+#[derive(Clone, Debug)]
+pub struct Level {
+    pub name: String,
+    pub world_map: WorldMap,
+    /// Posición (x, y) y ángulo inicial del jugador
+    pub player_start: (f32, f32, f32),
+    /// Segundos antes de activar al enemigo; None = nivel sin enemigo
+    pub enemy_delay: Option<f32>,
+    /// Celdas donde puede aparecer el enemigo; vacío = cualquier celda lejana
+    pub enemy_spawn_points: Vec<(usize, usize)>,
+    pub exit_position: (usize, usize),
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    /// Error en el contenido, con línea y columna empezando en 1
+    Parse { line: usize, column: usize, message: String },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(e) => write!(f, "no se pudo leer el archivo: {}", e),
+            LevelError::Parse { line, column, message } => {
+                write!(f, "línea {}, columna {}: {}", line, column, message)
+            }
+        }
+    }
+}
+
+fn parse_error(line: usize, column: usize, message: impl Into<String>) -> LevelError {
+    LevelError::Parse { line, column, message: message.into() }
+}
+
+pub fn load_level_file(path: &str) -> Result<Level, LevelError> {
+    let text = std::fs::read_to_string(path).map_err(LevelError::Io)?;
+    parse_level(&text)
+}
+
+pub fn save_level_file(path: &str, level: &Level) -> Result<(), LevelError> {
+    std::fs::write(path, serialize_level(level)).map_err(LevelError::Io)
+}
+
+pub fn parse_level(text: &str) -> Result<Level, LevelError> {
+    let lines: Vec<&str> = text.lines().collect();
+
+    let mut name = String::from("Sin nombre");
+    let mut header_start: Option<((f32, f32, f32), usize)> = None;
+    let mut enemy_delay = Some(DEFAULT_ENEMY_DELAY);
+    let mut map_line = None;
+
+    for (index, raw) in lines.iter().enumerate() {
+        let line_number = index + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(parse_error(line_number, 1, format!("se esperaba \"clave: valor\", se encontró \"{}\"", line)));
+        };
+        let key = key.trim();
+        // Columna (desde 1) donde empieza el valor, para los mensajes de error
+        let value_column = raw.trim_end().len() - value.trim_start().len() + 1;
+        let value = value.trim();
+
+        match key {
+            "map" => {
+                map_line = Some(index + 1);
+                break;
+            }
+            "name" => name = value.to_string(),
+            "player_start" => {
+                let numbers: Vec<&str> = value.split_whitespace().collect();
+                if numbers.len() != 2 && numbers.len() != 3 {
+                    return Err(parse_error(line_number, value_column, "player_start necesita \"x y\" o \"x y ángulo\""));
+                }
+                let mut parsed = [0.0; 3];
+                for (i, number) in numbers.iter().enumerate() {
+                    parsed[i] = number.parse().map_err(|_| {
+                        parse_error(line_number, value_column, format!("\"{}\" no es un número", number))
+                    })?;
+                }
+                header_start = Some(((parsed[0], parsed[1], parsed[2]), line_number));
+            }
+            "enemy_delay" => {
+                enemy_delay = if value == "never" {
+                    None
+                } else {
+                    let delay: f32 = value.parse().map_err(|_| {
+                        parse_error(line_number, value_column, format!("\"{}\" no es un número ni \"never\"", value))
+                    })?;
+                    if delay < 0.0 {
+                        return Err(parse_error(line_number, value_column, "enemy_delay no puede ser negativo"));
+                    }
+                    Some(delay)
+                };
+            }
+            _ => return Err(parse_error(line_number, 1, format!("clave desconocida \"{}\"", key))),
+        }
+    }
+
+    let Some(first_row) = map_line else {
+        return Err(parse_error(lines.len().max(1), 1, "falta la sección \"map:\""));
+    };
+
+    // Filas del mapa hasta el final, ignorando líneas vacías al final
+    let mut rows: Vec<&str> = lines[first_row..].iter().map(|row| row.trim_end()).collect();
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+
+    let height = rows.len();
+    let width = rows.first().map_or(0, |row| row.chars().count());
+    if height < 3 || width < 3 {
+        return Err(parse_error(first_row + 1, 1, "el mapa debe tener al menos 3x3 celdas"));
+    }
+
+    let mut world_map = WorldMap::new(width, height, Tile::Wall);
+    let mut marker_start = None;
+    let mut enemy_spawn_points = Vec::new();
+    let mut exit_position = None;
+
+    for (y, row) in rows.iter().enumerate() {
+        let line_number = first_row + y + 1;
+        let row_width = row.chars().count();
+        if row_width != width {
+            return Err(parse_error(
+                line_number,
+                row_width.min(width) + 1,
+                format!("la fila tiene {} columnas, se esperaban {}", row_width, width),
+            ));
+        }
+
+        for (x, symbol) in row.chars().enumerate() {
+            let tile = match symbol {
+                PLAYER_MARKER => {
+                    if marker_start.is_some() {
+                        return Err(parse_error(line_number, x + 1, "hay más de un inicio del jugador (P)"));
+                    }
+                    marker_start = Some((x, y));
+                    Tile::Empty
+                }
+                ENEMY_MARKER => {
+                    enemy_spawn_points.push((x, y));
+                    Tile::Empty
+                }
+                _ => Tile::from_symbol(symbol).ok_or_else(|| {
+                    parse_error(line_number, x + 1, format!("símbolo desconocido '{}'", symbol))
+                })?,
+            };
+
+            if tile == Tile::Exit {
+                if exit_position.is_some() {
+                    return Err(parse_error(line_number, x + 1, "hay más de una salida (X)"));
+                }
+                exit_position = Some((x, y));
+            }
+
+            world_map.set(x, y, tile);
+        }
+    }
+
+    let Some(exit_position) = exit_position else {
+        return Err(parse_error(first_row + 1, 1, "el mapa no tiene salida (X)"));
+    };
+
+    // Ubicación del inicio en el archivo, para reportar errores sobre ella
+    let (player_start, start_line, start_column) = match (header_start, marker_start) {
+        (Some(_), Some((x, y))) => {
+            return Err(parse_error(first_row + y + 1, x + 1, "el inicio está en player_start y también como P"));
+        }
+        (Some((start, line)), None) => (start, line, 1),
+        (None, Some((x, y))) => ((x as f32 + 0.5, y as f32 + 0.5, 0.0), first_row + y + 1, x + 1),
+        (None, None) => return Err(parse_error(1, 1, "falta el inicio del jugador (player_start o P)")),
+    };
+
+    let start_cell = (player_start.0.floor(), player_start.1.floor());
+    let walkable = start_cell.0 >= 0.0
+        && start_cell.1 >= 0.0
+        && world_map
+            .get(start_cell.0 as usize, start_cell.1 as usize)
+            .is_some_and(|tile| !tile.is_solid_for_player());
+    if !walkable {
+        return Err(parse_error(start_line, start_column, "el inicio del jugador no está en una celda libre"));
+    }
+
+    let report = validate(&world_map, (start_cell.0 as usize, start_cell.1 as usize), exit_position);
+    if report.exit_path_length.is_none() {
+        return Err(parse_error(
+            first_row + exit_position.1 + 1,
+            exit_position.0 + 1,
+            "la salida no se puede alcanzar desde el inicio",
+        ));
+    }
+
+    Ok(Level {
+        name,
+        world_map,
+        player_start,
+        enemy_delay,
+        enemy_spawn_points,
+        exit_position,
+    })
+}
+
+pub fn serialize_level(level: &Level) -> String {
+    let mut text = String::new();
+    let (x, y, angle) = level.player_start;
+
+    text.push_str(&format!("name: {}\n", level.name));
+    text.push_str(&format!("player_start: {} {} {}\n", x, y, angle));
+    match level.enemy_delay {
+        Some(delay) => text.push_str(&format!("enemy_delay: {}\n", delay)),
+        None => text.push_str("enemy_delay: never\n"),
+    }
+    text.push_str("map:\n");
+
+    for row in 0..level.world_map.height() {
+        for column in 0..level.world_map.width() {
+            let symbol = if level.enemy_spawn_points.contains(&(column, row)) {
+                ENEMY_MARKER
+            } else {
+                level.world_map.get(column, row).map_or('#', |tile| tile.props().symbol)
+            };
+            text.push(symbol);
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::GameState;
+
+    const SAMPLE: &str = "\
+# Nivel de prueba
+name: Pasillo
+enemy_delay: 4.5
+map:
+#######
+#P.%.X#
+#..E..#
+#######
+";
+
+    fn parse_error_position(text: &str) -> (usize, usize) {
+        match parse_level(text) {
+            Err(LevelError::Parse { line, column, .. }) => (line, column),
+            other => panic!("se esperaba un error de formato, se obtuvo {:?}", other.map(|l| l.name)),
+        }
+    }
+
+    #[test]
+    fn parses_header_and_markers() {
+        let level = parse_level(SAMPLE).unwrap();
+
+        assert_eq!(level.name, "Pasillo");
+        assert_eq!(level.enemy_delay, Some(4.5));
+        assert_eq!(level.player_start, (1.5, 1.5, 0.0));
+        assert_eq!(level.enemy_spawn_points, vec![(3, 2)]);
+        assert_eq!(level.exit_position, (5, 1));
+        assert_eq!(level.world_map.get(3, 1), Some(Tile::BloodyWall));
+        assert_eq!(level.world_map.get(3, 2), Some(Tile::Empty));
+    }
+
+    #[test]
+    fn generated_maps_round_trip() {
+        for seed in 0..20 {
+            let level = GameState::new(Some(seed)).current_level();
+            let reparsed = parse_level(&serialize_level(&level)).unwrap();
+
+            assert_eq!(reparsed.world_map, level.world_map);
+            assert_eq!(reparsed.player_start, level.player_start);
+            assert_eq!(reparsed.exit_position, level.exit_position);
+        }
+    }
+
+    #[test]
+    fn reports_line_and_column_of_bad_input() {
+        assert_eq!(parse_error_position("map:\n#####\n#P?X#\n#####\n"), (3, 3));
+        assert_eq!(parse_error_position("map:\n#####\n#PX#\n#####\n"), (3, 5));
+        assert_eq!(parse_error_position("speed: 3\nmap:\n###\n#P#\n###\n"), (1, 1));
+        assert_eq!(parse_error_position("enemy_delay: soon\nmap:\n#####\n#PX.#\n#####\n"), (1, 14));
+        assert_eq!(parse_error_position("map:\n#####\n#P#X#\n#####\n"), (3, 4));
+        assert_eq!(parse_error_position("name: Sin mapa\n"), (1, 1));
+    }
+}
@@ -0,0 +1,300 @@
+// Luces fluorescentes del techo y mapa de luz por celda. Cada luz ilumina las celdas
+// que ve (las paredes tapan la luz) y pierde fuerza con la distancia. Lo que llega de
+// cada luz a cada celda se calcula una sola vez al armar el mapa; en cada cuadro solo
+// se vuelve a sumar con el brillo actual de las luces, que parpadean o fallan.
+
+use std::collections::HashMap;
+use crate::raycaster::has_line_of_sight;
+use crate::world_map::TileMap;
+
+// Celdas hasta donde llega una luz, y su brillo justo debajo
+const LIGHT_RADIUS: f32 = 6.0;
+const LIGHT_INTENSITY: f32 = 0.85;
+// Tope de la suma de varias luces en una celda
+const MAX_LEVEL: f32 = 1.3;
+// Distancia hasta la que se oye el zumbido de una luz, y cuánto queda de él si su luz
+// no llega hasta quien escucha (hay una pared en medio)
+const HUM_RANGE: f32 = 8.0;
+const HUM_THROUGH_WALLS: f32 = 0.25;
+// Medio ancho y medio largo del panel del tubo visto desde abajo, en celdas
+const FIXTURE_HALF_SIZE: (f32, f32) = (0.3, 0.12);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LightKind {
+    Steady,
+    /// Anda mal: a ratos tiembla y se apaga
+    Flickering,
+    /// Quemada: casi siempre apagada, con algún chispazo
+    Broken,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CeilingLight {
+    /// Celda sobre la que cuelga
+    pub x: i32,
+    pub y: i32,
+    pub kind: LightKind,
+    /// Desfasa el parpadeo de cada luz
+    pub seed: u32,
+}
+
+impl CeilingLight {
+    /// Fracción de su luz que da en el instante `time` (segundos)
+    pub fn brightness(&self, time: f32) -> f32 {
+        match self.kind {
+            LightKind::Steady => 1.0,
+            LightKind::Flickering => {
+                // Ventanas de un segundo y medio en las que tiembla, y entre ellas algún parpadeo suelto
+                let unstable = hash(self.seed ^ 0x9E37_79B9, (time * 0.7) as u32) < 0.3;
+                let flicker = hash(self.seed, (time * 12.0) as u32);
+                if unstable && flicker < 0.5 {
+                    0.1
+                } else if flicker < 0.03 {
+                    0.3
+                } else {
+                    1.0
+                }
+            }
+            LightKind::Broken => {
+                if hash(self.seed, (time * 20.0) as u32) < 0.005 { 0.7 } else { 0.0 }
+            }
+        }
+    }
+}
+
+pub struct LightMap {
+    // Celda de la esquina noroeste y tamaño de la zona cubierta
+    origin: (i32, i32),
+    width: usize,
+    height: usize,
+    lights: Vec<CeilingLight>,
+    // Por cada luz: celdas a las que llega (índice en levels) y qué fracción de su luz
+    reach: Vec<Vec<(usize, f32)>>,
+    // Brillo actual de cada luz, y qué luz cuelga sobre cada celda
+    brightness: Vec<f32>,
+    fixtures: Vec<Option<usize>>,
+    levels: Vec<f32>,
+    time: f32,
+}
+
+impl LightMap {
+    /// Mapa sin luces: todo a oscuras
+    pub fn empty() -> Self {
+        Self {
+            origin: (0, 0),
+            width: 0,
+            height: 0,
+            lights: Vec::new(),
+            reach: Vec::new(),
+            brightness: Vec::new(),
+            fixtures: Vec::new(),
+            levels: Vec::new(),
+            time: 0.0,
+        }
+    }
+
+    /// Calcula a qué celdas de la zona (origin, width x height) llega cada luz
+    pub fn build(map: &dyn TileMap, origin: (i32, i32), width: usize, height: usize, lights: Vec<CeilingLight>) -> Self {
+        let mut light_map = Self {
+            origin,
+            width,
+            height,
+            fixtures: vec![None; width * height],
+            levels: vec![0.0; width * height],
+            ..Self::empty()
+        };
+        light_map.reach = lights.iter().map(|light| light_map.light_reach(map, light)).collect();
+        for (light_index, light) in lights.iter().enumerate() {
+            if let Some(index) = light_map.index(light.x, light.y) {
+                light_map.fixtures[index] = Some(light_index);
+            }
+        }
+        light_map.brightness = vec![0.0; lights.len()];
+        light_map.lights = lights;
+        light_map.update(0.0);
+        light_map
+    }
+
+    /// Sigue el parpadeo desde donde iba `previous`, para que recalcular el mapa al
+    /// cargar chunks no reinicie las luces
+    pub fn continue_from(&mut self, previous: &LightMap) {
+        self.time = previous.time;
+        self.update(0.0);
+    }
+
+    /// Avanza el parpadeo de las luces y vuelve a sumar la luz de cada celda
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+        self.levels.fill(0.0);
+        for ((light, reach), current) in self.lights.iter().zip(&self.reach).zip(&mut self.brightness) {
+            *current = light.brightness(self.time);
+            if *current <= 0.0 {
+                continue;
+            }
+            for &(index, fraction) in reach {
+                self.levels[index] += *current * LIGHT_INTENSITY * fraction;
+            }
+        }
+        for level in &mut self.levels {
+            *level = level.min(MAX_LEVEL);
+        }
+    }
+
+    /// Luz en el punto (x, y), interpolada entre los centros de las celdas vecinas
+    pub fn level_at(&self, x: f32, y: f32) -> f32 {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (cell_x, cell_y) = (x.floor() as i32, y.floor() as i32);
+        let (fx, fy) = (x - x.floor(), y - y.floor());
+
+        let top = self.cell_level(cell_x, cell_y) * (1.0 - fx) + self.cell_level(cell_x + 1, cell_y) * fx;
+        let bottom = self.cell_level(cell_x, cell_y + 1) * (1.0 - fx) + self.cell_level(cell_x + 1, cell_y + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// Brillo actual del tubo cuyo panel se ve en el techo sobre el punto (x, y), si hay uno
+    pub fn fixture_at(&self, x: f32, y: f32) -> Option<f32> {
+        let light = self.fixtures[self.index(x.floor() as i32, y.floor() as i32)?]?;
+        let inside = (x - x.floor() - 0.5).abs() < FIXTURE_HALF_SIZE.0 && (y - y.floor() - 0.5).abs() < FIXTURE_HALF_SIZE.1;
+        inside.then_some(self.brightness[light])
+    }
+
+    /// Qué tan fuerte se oye el zumbido de la luz encendida más cercana al punto, de 0 a 1.
+    /// Las luces que no llegan hasta el punto se oyen apagadas a través de las paredes.
+    pub fn hum_at(&self, x: f32, y: f32) -> f32 {
+        let cell = self.index(x.floor() as i32, y.floor() as i32);
+        self.lights
+            .iter()
+            .zip(&self.reach)
+            .zip(&self.brightness)
+            .map(|((light, reach), brightness)| {
+                let distance = ((light.x as f32 + 0.5 - x).powi(2) + (light.y as f32 + 0.5 - y).powi(2)).sqrt();
+                let closeness = (1.0 - distance / HUM_RANGE).max(0.0);
+                if closeness == 0.0 {
+                    return 0.0;
+                }
+                let in_sight = cell.is_some_and(|cell| reach.iter().any(|&(index, _)| index == cell));
+                closeness * brightness * if in_sight { 1.0 } else { HUM_THROUGH_WALLS }
+            })
+            .fold(0.0, f32::max)
+    }
+
+    fn cell_level(&self, x: i32, y: i32) -> f32 {
+        self.index(x, y).map_or(0.0, |index| self.levels[index])
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (local_x, local_y) = (x - self.origin.0, y - self.origin.1);
+        if local_x < 0 || local_y < 0 || local_x as usize >= self.width || local_y as usize >= self.height {
+            return None;
+        }
+        Some(local_y as usize * self.width + local_x as usize)
+    }
+
+    // Celdas libres que la luz ve, con su fracción según la distancia. Las paredes toman
+    // la luz de la celda libre más iluminada que tienen al lado, así su cara se ve iluminada.
+    fn light_reach(&self, map: &dyn TileMap, light: &CeilingLight) -> Vec<(usize, f32)> {
+        let radius = LIGHT_RADIUS.ceil() as i32;
+        let (light_x, light_y) = (light.x as f32 + 0.5, light.y as f32 + 0.5);
+        let mut open = HashMap::new();
+        let mut walls = Vec::new();
+
+        for y in light.y - radius..=light.y + radius {
+            for x in light.x - radius..=light.x + radius {
+                let (Some(index), Some(tile)) = (self.index(x, y), map.tile(x, y)) else {
+                    continue;
+                };
+                if tile.blocks_sight() {
+                    walls.push((x, y, index));
+                    continue;
+                }
+                let (cell_x, cell_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                let distance = ((cell_x - light_x).powi(2) + (cell_y - light_y).powi(2)).sqrt();
+                if distance < LIGHT_RADIUS && has_line_of_sight(map, light_x, light_y, cell_x, cell_y) {
+                    open.insert((x, y), (index, (1.0 - distance / LIGHT_RADIUS).powi(2)));
+                }
+            }
+        }
+
+        let mut reach: Vec<(usize, f32)> = open.values().copied().collect();
+        for (x, y, index) in walls {
+            let fraction = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .iter()
+                .filter_map(|(dx, dy)| open.get(&(x + dx, y + dy)).map(|&(_, fraction)| fraction))
+                .fold(0.0, f32::max);
+            if fraction > 0.0 {
+                reach.push((index, fraction));
+            }
+        }
+        reach
+    }
+}
+
+// Número pseudoaleatorio en [0, 1) que depende solo de (seed, step)
+fn hash(seed: u32, step: u32) -> f32 {
+    let mut z = seed.wrapping_mul(0x9E37_79B9) ^ step.wrapping_mul(0x85EB_CA6B);
+    z = (z ^ (z >> 16)).wrapping_mul(0x7FEB_352D);
+    z = (z ^ (z >> 15)).wrapping_mul(0x846C_A68B);
+    z ^= z >> 16;
+    (z >> 8) as f32 / (1u32 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_map::WorldMap;
+
+    fn light(x: i32, y: i32, kind: LightKind) -> CeilingLight {
+        CeilingLight { x, y, kind, seed: 7 }
+    }
+
+    #[test]
+    fn walls_block_the_light() {
+        let map = WorldMap::from_rows(&[
+            "#########",
+            "#...#...#",
+            "#...#...#",
+            "#...#...#",
+            "#########",
+        ]);
+        let light_map = LightMap::build(&map, (0, 0), map.width(), map.height(), vec![light(2, 2, LightKind::Steady)]);
+
+        assert!((light_map.cell_level(2, 2) - LIGHT_INTENSITY).abs() < 1e-5);
+        assert!(light_map.cell_level(3, 1) > 0.0 && light_map.cell_level(3, 1) < light_map.cell_level(2, 2));
+        // La pared del medio se ilumina del lado de la luz pero no deja pasar nada
+        assert!(light_map.cell_level(4, 2) > 0.0);
+        assert_eq!(light_map.cell_level(6, 2), 0.0);
+        assert_eq!(light_map.level_at(6.5, 2.5), 0.0);
+
+        // Detrás de la pared el zumbido llega apagado
+        let near = light_map.hum_at(3.5, 2.5);
+        let behind = light_map.hum_at(5.5, 2.5);
+        assert!(near > 0.0);
+        assert!(behind > 0.0 && behind <= near * HUM_THROUGH_WALLS);
+    }
+
+    #[test]
+    fn broken_and_flickering_lights_change_over_time() {
+        let map = WorldMap::from_rows(&["#####", "#...#", "#...#", "#...#", "#####"]);
+        let lights = vec![light(2, 2, LightKind::Flickering)];
+        let mut light_map = LightMap::build(&map, (0, 0), map.width(), map.height(), lights);
+
+        // En un minuto la luz que parpadea pasa por momentos apagada y momentos a pleno
+        let mut levels = Vec::new();
+        for _ in 0..3600 {
+            light_map.update(1.0 / 60.0);
+            levels.push(light_map.cell_level(2, 2));
+        }
+        assert!(levels.iter().any(|&level| level < LIGHT_INTENSITY * 0.5));
+        assert!(levels.iter().filter(|&&level| level == LIGHT_INTENSITY).count() > 1800);
+
+        let broken = LightMap::build(&map, (0, 0), map.width(), map.height(), vec![light(2, 2, LightKind::Broken)]);
+        assert_eq!(broken.cell_level(2, 2), 0.0);
+        assert_eq!(broken.hum_at(2.5, 2.5), 0.0);
+
+        // Al recalcular el mapa el parpadeo sigue donde iba
+        let mut rebuilt = LightMap::build(&map, (0, 0), map.width(), map.height(), vec![light(2, 2, LightKind::Flickering)]);
+        rebuilt.continue_from(&light_map);
+        assert_eq!(rebuilt.time, light_map.time);
+        assert_eq!(rebuilt.brightness, light_map.brightness);
+    }
+}
@@ -5,23 +5,62 @@
 /// Genser Catalán -- 23401
 
 use macroquad::prelude::*;
-use macroquad::audio::{load_sound, play_sound, play_sound_once, stop_sound, Sound, PlaySoundParams};
+use macroquad::audio::{load_sound, play_sound, play_sound_once, set_sound_volume, stop_sound, Sound, PlaySoundParams};
 use std::collections::HashMap;
 
 mod player;
+mod campaign;
+mod chunked_world;
+mod difficulty;
+mod director;
 mod textures;
 mod game_state;
+mod level_file;
+mod lighting;
+mod map_generators;
+mod map_validation;
 mod minimap;
+mod noise;
+mod pathfinding;
 mod enemy;
+mod enemy_ai;
+mod enemy_archetypes;
+mod flashlight;
+mod input;
+mod raycaster;
+mod renderer;
+mod settings;
+mod sprites;
+mod tile;
+mod world_map;
 
-use player::Player;
+use player::{Player, Stamina};
+use flashlight::{Flashlight, BATTERY_PICKUP_CHARGE};
 use textures::{load_textures, TextureManager};
-use game_state::{GameState, Screen};
+use game_state::{GameState, Screen, ENEMY_ALERT_TIME, LEVEL_TRANSITION_TIME};
+use level_file::{load_level_file, save_level_file};
+use map_generators::GeneratorKind;
 use minimap::Minimap;
-use enemy::Enemy;
+use noise::NoiseField;
+use difficulty::Difficulty;
+use director::Tension;
+use enemy::{load_enemy_textures, Enemy};
+use enemy_ai::AiState;
+use renderer::Renderer;
+use settings::{Settings, SettingsOption, SETTINGS_FILE};
+use input::{is_bindable, Action, Bindings, Input, BINDINGS_FILE, MAX_KEYS};
+use sprites::Sprite;
+use tile::TileTrigger;
+use world_map::TileMap;
 
 const SCREEN_WIDTH: f32 = 800.0;
 const SCREEN_HEIGHT: f32 = 600.0;
+// Celdas alrededor del jugador en las que se buscan objetos del mapa para dibujarlos
+const TILE_SPRITE_RANGE: i32 = 16;
+// Altura en el mundo de los objetos del mapa (1.0 = una pared)
+const TILE_SPRITE_SIZE: f32 = 0.3;
+// Volumen del ambiente lejos de toda lámpara y justo debajo de una encendida
+const AMBIENT_VOLUME: (f32, f32) = (0.15, 0.6);
 
 #[macroquad::main("Backrooms - Escape the Liminal")]
 async fn main() {
@@ -32,18 +71,36 @@ async fn main() {
     let screamer_texture = load_screamer_texture().await;
     let screamer2_texture = load_screamer2_texture().await; 
     let screamer3_texture = load_screamer3_texture().await; 
-    let mut game_state = GameState::new();
-    let mut player = Player::new(2.5, 2.5, 0.0);
-    let mut enemy = Enemy::new();
-    let minimap = Minimap::new();
-    
-    // Cargar textura del enemigo
-    enemy.load_texture().await;
+    let mut game_state = GameState::new(parse_seed_arg());
+    game_state.settings = Settings::load(SETTINGS_FILE);
+    game_state.input = Input::new(Bindings::load(BINDINGS_FILE));
+    game_state.input.enable_gamepads();
+    apply_generator_arg(&mut game_state);
+    apply_level_args(&mut game_state);
+    let (start_x, start_y, start_angle) = game_state.player_start;
+    let mut player = Player::new(start_x, start_y, start_angle);
+    let mut enemies: Vec<Enemy> = Vec::new();
+    let mut minimap = Minimap::new();
+    let mut renderer = Renderer::new(SCREEN_WIDTH, SCREEN_HEIGHT);
+    // Fila elegida en la pantalla de opciones y en la de controles, lugar de tecla elegido
+    // en la fila, si se espera la tecla o botón nuevo, y la acción que impidió el último cambio
+    let mut options_row = 0;
+    let mut controls_row = 0;
+    let mut controls_slot = 0;
+    let mut rebinding = false;
+    let mut rebind_blocked = None;
+    
+    // Cargar los sprites de los tipos de enemigo
+    let enemy_textures = load_enemy_textures().await;
     
     let mut background_music_playing = false;
-    let mut gameplay_music_playing = false; 
+    // Sonido de ambiente en bucle que suena durante la partida, si hay alguno
+    let mut gameplay_music: Option<&str> = None;
     let mut footstep_playing = false;
-    let mut enemy_sound_playing = false;
+    // Respiración agitada en bucle mientras el jugador está agotado
+    let mut breathing_playing = false;
+    // Sonido en bucle del enemigo más cercano, si alguno está cerca
+    let mut enemy_sound: Option<&str> = None;
     
     // Configurar el mouse para captura relativa
     set_cursor_grab(false);
@@ -51,6 +108,7 @@ async fn main() {
     
     loop {
         let dt = get_frame_time();
+        game_state.input.update();
         
         match game_state.current_screen {
             Screen::Menu => {
@@ -58,7 +116,7 @@ async fn main() {
                 show_mouse(true);
                 
                 // Detener todos los sonidos cuando estamos en el menú
-                stop_all_game_sounds(&sounds, &mut footstep_playing, &mut enemy_sound_playing, &mut gameplay_music_playing);
+                stop_all_game_sounds(&sounds, &mut footstep_playing, &mut breathing_playing, &mut enemy_sound, &mut gameplay_music);
                 
                 // Reproducir música de fondo en el menú
                 if !background_music_playing {
@@ -75,13 +133,43 @@ async fn main() {
                     }
                 }
                 
-                handle_menu(&mut game_state).await;
-                draw_menu(&texture_manager);
+                handle_menu(&mut game_state, &mut player, &mut enemies, &sounds, &mut enemy_sound).await;
+                draw_menu(&texture_manager, game_state.difficulty, &game_state.input.bindings);
+            }
+            Screen::Options => {
+                handle_options(&mut game_state, &mut options_row);
+                draw_options(&game_state.settings, options_row, &game_state.input.bindings);
+            }
+            Screen::Controls => {
+                handle_controls(&mut game_state, (&mut controls_row, &mut controls_slot), &mut rebinding, &mut rebind_blocked);
+                draw_controls(&game_state.input.bindings, (controls_row, controls_slot), rebinding, rebind_blocked);
+            }
+            Screen::Game if game_state.paused => {
+                set_cursor_grab(false);
+                show_mouse(true);
+                stop_all_game_sounds(&sounds, &mut footstep_playing, &mut breathing_playing, &mut enemy_sound, &mut gameplay_music);
+                
+                if game_state.input.pressed(Action::Pause) {
+                    game_state.paused = false;
+                }
+                
+                draw_game(&player, &enemies, &game_state, &texture_manager, &minimap, &mut renderer);
+                draw_paused(&game_state.input.bindings);
             }
             Screen::Game => {
                 set_cursor_grab(true);
                 show_mouse(false);
                 
+                // Modo de depuración del minimapa
+                if game_state.input.pressed(Action::DebugMinimap) {
+                    minimap.debug = !minimap.debug;
+                }
+                
+                // La pausa congela la partida desde el cuadro siguiente
+                if game_state.input.pressed(Action::Pause) && !game_state.escaped && !game_state.game_over {
+                    game_state.paused = true;
+                }
+                
                 // Detener música de menú al entrar al juego
                 if background_music_playing {
                     if let Some(bg_music) = sounds.get("background") {
@@ -90,9 +178,13 @@ async fn main() {
                     background_music_playing = false;
                 }
                 
-                // Iniciar música de gameplay
-                if !gameplay_music_playing {
-                    if let Some(gameplay_sound) = sounds.get("gameplay_sound") {
+                // Iniciar el ambiente del nivel, cambiándolo si se pasó a otro nivel
+                let ambient = ambient_sound_name(&sounds, game_state.level_def().ambient_sound);
+                if gameplay_music != Some(ambient) {
+                    if let Some(previous) = gameplay_music.take().and_then(|name| sounds.get(name)) {
+                        stop_sound(previous);
+                    }
+                    if let Some(gameplay_sound) = sounds.get(ambient) {
                         play_sound(
                             gameplay_sound,
                             PlaySoundParams {
@@ -100,33 +192,42 @@ async fn main() {
                                 volume: 0.5, 
                             },
                         );
-                        gameplay_music_playing = true;
-                        println!("Música de gameplay iniciada");
+                        gameplay_music = Some(ambient);
+                        println!("Música de gameplay iniciada: {}", ambient);
                     }
                 }
                 
+                // El zumbido del ambiente sube cerca de las lámparas encendidas
+                if let Some(gameplay_sound) = gameplay_music.and_then(|name| sounds.get(name)) {
+                    let hum = game_state.light_map.hum_at(player.x, player.y);
+                    set_sound_volume(gameplay_sound, AMBIENT_VOLUME.0 + (AMBIENT_VOLUME.1 - AMBIENT_VOLUME.0) * hum);
+                }
+                
                 if game_state.escaped {
                     // Detener todos los sonidos al ganar
-                    stop_all_game_sounds(&sounds, &mut footstep_playing, &mut enemy_sound_playing, &mut gameplay_music_playing);
+                    stop_all_game_sounds(&sounds, &mut footstep_playing, &mut breathing_playing, &mut enemy_sound, &mut gameplay_music);
                     
                     handle_victory(&mut game_state, &sounds).await;
-                    draw_victory();
+                    draw_victory(game_state.seed, game_state.difficulty, &game_state.input.bindings);
                 } else {
                     // Actualizar el estado del juego
                     game_state.update(dt);
                     
                     if !game_state.game_over {
                         // Actualizar juego normal
+                        spawn_enemies(&mut enemies, &enemy_textures, &player, &mut game_state, dt);
                         update_game(
                             &mut player, 
-                            &mut enemy,
+                            &mut enemies,
                             &mut game_state, 
                             &sounds, 
+                            &renderer,
                             &mut footstep_playing,
-                            &mut enemy_sound_playing
+                            &mut enemy_sound
                         ).await;
+                        update_breathing(&sounds, &player, &mut breathing_playing);
                         
-                        draw_game(&player, &enemy, &game_state, &texture_manager, &minimap);
+                        draw_game(&player, &enemies, &game_state, &texture_manager, &minimap, &mut renderer);
                         
                         // Dibujar screamers si están activos
                         if game_state.screamer_active {
@@ -149,14 +250,14 @@ async fn main() {
                 show_mouse(true);
                 
                 // Detener todos los sonidos durante game over
-                stop_all_game_sounds(&sounds, &mut footstep_playing, &mut enemy_sound_playing, &mut gameplay_music_playing);
+                stop_all_game_sounds(&sounds, &mut footstep_playing, &mut breathing_playing, &mut enemy_sound, &mut gameplay_music);
                 
                 // Manejar input para regresar al menú
-                if is_key_pressed(KeyCode::Space) {
+                if game_state.input.pressed(Action::Interact) {
                     game_state.reset();
                 }
                 
-                draw_game_over_with_input();
+                draw_game_over_with_input(game_state.seed, game_state.difficulty, &game_state.input.bindings);
             }
         }
         
@@ -167,8 +268,9 @@ async fn main() {
 fn stop_all_game_sounds(
     sounds: &HashMap<&str, Sound>,
     footstep_playing: &mut bool,
-    enemy_sound_playing: &mut bool,
-    gameplay_music_playing: &mut bool
+    breathing_playing: &mut bool,
+    enemy_sound: &mut Option<&str>,
+    gameplay_music: &mut Option<&str>
 ) {
     if *footstep_playing {
         if let Some(footstep) = sounds.get("footstep") {
@@ -177,18 +279,61 @@ fn stop_all_game_sounds(
         *footstep_playing = false;
     }
     
-    if *enemy_sound_playing {
-        if let Some(enemy_bg) = sounds.get("enemigoBackground") {
-            stop_sound(enemy_bg);
+    if *breathing_playing {
+        if let Some(breathing) = sounds.get("heavy_breathing") {
+            stop_sound(breathing);
         }
-        *enemy_sound_playing = false;
+        *breathing_playing = false;
     }
     
-    if *gameplay_music_playing {
-        if let Some(gameplay_sound) = sounds.get("gameplay_sound") {
-            stop_sound(gameplay_sound);
-        }
-        *gameplay_music_playing = false;
+    stop_enemy_sound(sounds, enemy_sound);
+    
+    if let Some(gameplay_sound) = gameplay_music.take().and_then(|name| sounds.get(name)) {
+        stop_sound(gameplay_sound);
+    }
+}
+
+// Respiración agitada mientras el jugador está agotado
+fn update_breathing(sounds: &HashMap<&str, Sound>, player: &Player, breathing_playing: &mut bool) {
+    let Some(breathing) = sounds.get("heavy_breathing") else {
+        return;
+    };
+    if player.stamina.exhausted && !*breathing_playing {
+        play_sound(
+            breathing,
+            PlaySoundParams {
+                looped: true,
+                volume: 0.5,
+            },
+        );
+        *breathing_playing = true;
+    } else if !player.stamina.exhausted && *breathing_playing {
+        stop_sound(breathing);
+        *breathing_playing = false;
+    }
+}
+
+fn stop_enemy_sound(sounds: &HashMap<&str, Sound>, enemy_sound: &mut Option<&str>) {
+    if let Some(enemy_bg) = enemy_sound.take().and_then(|name| sounds.get(name)) {
+        stop_sound(enemy_bg);
+    }
+}
+
+// Sonido en bucle de un tipo de enemigo; si su archivo no se cargó se usa el general
+fn enemy_sound_name<'a>(sounds: &HashMap<&'a str, Sound>, name: &'a str) -> &'a str {
+    if sounds.contains_key(name) {
+        name
+    } else {
+        "enemigoBackground"
+    }
+}
+
+// Sonido de ambiente de un nivel; si su archivo no se cargó se usa el ambiente general
+fn ambient_sound_name<'a>(sounds: &HashMap<&'a str, Sound>, name: &'a str) -> &'a str {
+    if sounds.contains_key(name) {
+        name
+    } else {
+        "gameplay_sound"
     }
 }
 
@@ -312,7 +457,7 @@ async fn load_screamer3_texture() -> Option<Texture2D> {
     None
 }
 
-fn draw_game_over_with_input() {
+fn draw_game_over_with_input(seed: u64, difficulty: Difficulty, bindings: &Bindings) {
     clear_background(Color::from_rgba(20, 0, 0, 255));
     
     let game_over_text = "GAME OVER";
@@ -337,16 +482,100 @@ fn draw_game_over_with_input() {
         WHITE,
     );
     
-    let instruction = "Press SPACE to return to menu";
+    let instruction = format!("Press {} to return to menu", key_hint(bindings, Action::Interact));
     let instruction_size = 18.0;
-    let instruction_width = measure_text(instruction, None, instruction_size as u16, 1.0).width;
+    let instruction_width = measure_text(&instruction, None, instruction_size as u16, 1.0).width;
     draw_text(
-        instruction,
+        &instruction,
         (SCREEN_WIDTH - instruction_width) / 2.0,
         370.0,
         instruction_size,
         Color::from_rgba(200, 200, 200, 255),
     );
+    
+    draw_run_info(seed, difficulty, 420.0);
+}
+
+// Semilla del mapa para poder repetir la partida con --seed, y la dificultad jugada
+fn draw_run_info(seed: u64, difficulty: Difficulty, y: f32) {
+    let seed_text = format!("Seed: {} - Difficulty: {}", seed, difficulty.name());
+    let seed_size = 16.0;
+    let seed_width = measure_text(&seed_text, None, seed_size as u16, 1.0).width;
+    draw_text(
+        &seed_text,
+        (SCREEN_WIDTH - seed_width) / 2.0,
+        y,
+        seed_size,
+        Color::from_rgba(150, 150, 150, 255),
+    );
+}
+
+// Valor de "--nombre <valor>" o "--nombre=<valor>" en la línea de comandos
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let prefix = format!("{}=", name);
+    
+    for (i, arg) in args.iter().enumerate() {
+        if arg == name {
+            return args.get(i + 1).cloned();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    
+    None
+}
+
+// Lee la semilla de "--seed <n>" o "--seed=<n>" en la línea de comandos
+fn parse_seed_arg() -> Option<u64> {
+    let value = arg_value("--seed")?;
+    match value.parse() {
+        Ok(seed) => {
+            println!("Usando semilla fija: {}", seed);
+            Some(seed)
+        }
+        Err(_) => {
+            println!("✗ Semilla inválida: {}", value);
+            None
+        }
+    }
+}
+
+// "--generator <maze|rooms|caves|office>" usa ese generador en todos los niveles
+fn apply_generator_arg(game_state: &mut GameState) {
+    let Some(name) = arg_value("--generator") else {
+        return;
+    };
+    
+    match GeneratorKind::from_name(&name) {
+        Some(generator) => {
+            println!("Usando generador fijo: {}", generator.name());
+            game_state.force_generator(Some(generator));
+        }
+        None => {
+            let names: Vec<&str> = GeneratorKind::ALL.iter().map(|kind| kind.name()).collect();
+            println!("✗ Generador desconocido: {} (opciones: {})", name, names.join(", "));
+        }
+    }
+}
+
+// "--level <archivo>" juega un nivel hecho a mano y "--save-level <archivo>"
+// guarda el nivel actual (generado o cargado) para editarlo después
+fn apply_level_args(game_state: &mut GameState) {
+    if let Some(path) = arg_value("--level") {
+        match load_level_file(&path) {
+            Ok(level) => game_state.load_level(level),
+            Err(e) => println!("✗ No se pudo cargar el nivel {}: {}", path, e),
+        }
+    }
+    
+    if let Some(path) = arg_value("--save-level") {
+        match save_level_file(&path, &game_state.current_level()) {
+            Ok(()) => println!("✓ Nivel guardado en {}", path),
+            Err(e) => println!("✗ No se pudo guardar el nivel en {}: {}", path, e),
+        }
+    }
 }
 
 fn draw_screamer(screamer_texture: &Option<Texture2D>) {
@@ -641,17 +870,27 @@ async fn load_sounds() -> HashMap<&'static str, Sound> {
     println!("Directorio de trabajo actual: {:?}", std::env::current_dir());
     
     // Audios a cargar (incluyendo los nuevos sonidos del enemigo)
-    let sound_files = vec![
+    let sound_files = [
         ("footstep", "/assets/sounds/footstep.wav"),
         ("scream", "/assets/sounds/scream.wav"),
         ("screamer2", "/assets/sounds/screamer2.wav"), 
         ("scream3", "/assets/sounds/scream3.wav"), 
         ("enemigoBackground", "/assets/sounds/enemigoBackground.wav"), 
+        ("enemy_alert", "/assets/sounds/enemy_alert.wav"),
+        ("enemy_lurker", "/assets/sounds/enemy_lurker.wav"),
+        ("enemy_howler", "/assets/sounds/enemy_howler.wav"),
+        ("enemy_stalker", "/assets/sounds/enemy_stalker.wav"),
         ("background", "/assets/sounds/background.wav"),
         ("gameplay_sound", "/assets/sounds/gameplay_sound.wav"),
+        ("ambient_garage", "/assets/sounds/ambient_garage.wav"),
+        ("ambient_pipes", "/assets/sounds/ambient_pipes.wav"),
         ("victory", "/assets/sounds/victory.wav"),
+        ("heavy_breathing", "/assets/sounds/heavy_breathing.wav"),
+        ("flashlight_click", "/assets/sounds/flashlight_click.wav"),
+        ("battery_pickup", "/assets/sounds/battery_pickup.wav"),
     ];
     
+    let total_sounds = sound_files.len();
     for (name, path) in sound_files {
         if std::path::Path::new(path).exists() {
             match load_sound(path).await {
@@ -692,18 +931,243 @@ async fn load_sounds() -> HashMap<&'static str, Sound> {
         }
     }
     
-    println!("Sonidos cargados: {}/{}", sounds.len(), 8);
+    println!("Sonidos cargados: {}/{}", sounds.len(), total_sounds);
     sounds
 }
 
-async fn handle_menu(game_state: &mut GameState) {
-    if is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Enter) {
-        game_state.start_game();
+async fn handle_menu<'a>(
+    game_state: &mut GameState,
+    player: &mut Player,
+    enemies: &mut Vec<Enemy>,
+    sounds: &HashMap<&'a str, Sound>,
+    enemy_sound: &mut Option<&'a str>,
+) {
+    let input = &game_state.input;
+    if input.pressed(Action::Interact) {
+        begin_run(game_state, player, enemies, (sounds, enemy_sound), GameState::start_game);
+    } else if input.pressed(Action::Endless) {
+        begin_run(game_state, player, enemies, (sounds, enemy_sound), GameState::start_endless);
+    } else if input.pressed(Action::MenuLeft) {
+        game_state.set_difficulty(game_state.difficulty.step(-1));
+    } else if input.pressed(Action::MenuRight) {
+        game_state.set_difficulty(game_state.difficulty.step(1));
+    } else if input.pressed(Action::Options) {
+        game_state.current_screen = Screen::Options;
+    }
+}
+
+// Empieza una partida nueva con `start`: los enemigos y su sonido de la anterior se
+// descartan, así el plantel del nivel nuevo aparece completo
+fn begin_run(
+    game_state: &mut GameState,
+    player: &mut Player,
+    enemies: &mut Vec<Enemy>,
+    (sounds, enemy_sound): (&HashMap<&str, Sound>, &mut Option<&str>),
+    start: fn(&mut GameState),
+) {
+    enemies.clear();
+    stop_enemy_sound(sounds, enemy_sound);
+    start(game_state);
+    player.set_stamina(game_state.difficulty.settings().stamina);
+    player.flashlight = Flashlight::new(game_state.seed);
+    player.respawn(game_state.player_start);
+}
+
+// Arriba/abajo elige la opción, izquierda/derecha la cambia. La última fila lleva a
+// la pantalla de controles. Al volver al menú se guarda la configuración.
+fn handle_options(game_state: &mut GameState, row: &mut usize) {
+    let input = &game_state.input;
+    let option = SettingsOption::ALL.get(*row).copied();
+    if input.pressed(Action::MenuUp) {
+        *row = row.saturating_sub(1);
+    } else if input.pressed(Action::MenuDown) {
+        *row = (*row + 1).min(SettingsOption::ALL.len());
+    } else if input.pressed(Action::Pause) || input.pressed(Action::Options) {
+        match game_state.settings.save(SETTINGS_FILE) {
+            Ok(()) => println!("✓ Configuración guardada en {}", SETTINGS_FILE),
+            Err(e) => println!("✗ No se pudo guardar la configuración en {}: {}", SETTINGS_FILE, e),
+        }
+        game_state.current_screen = Screen::Menu;
+    } else if let Some(option) = option {
+        if input.pressed(Action::MenuLeft) {
+            game_state.settings.adjust(option, -1);
+        } else if input.pressed(Action::MenuRight) || input.pressed(Action::Interact) {
+            game_state.settings.adjust(option, 1);
+        }
+    } else if input.pressed(Action::MenuRight) || input.pressed(Action::Interact) {
+        game_state.current_screen = Screen::Controls;
+    }
+}
+
+// Arriba/abajo elige la acción e Interact espera la tecla o botón nuevo: una tecla
+// reemplaza las teclas de la acción y un botón, su botón del control. Al volver se
+// guardan los controles.
+fn handle_controls(game_state: &mut GameState, (row, slot): (&mut usize, &mut usize), rebinding: &mut bool, blocked: &mut Option<Action>) {
+    let action = Action::ALL[*row];
+    let input = &mut game_state.input;
+    if *rebinding {
+        // La pausa cancela el cambio en vez de asignarse
+        let result = if input.pressed(Action::Pause) {
+            Ok(())
+        } else if let Some(key) = get_last_key_pressed().filter(|&key| is_bindable(key)) {
+            input.bindings.bind_key(action, *slot, key)
+        } else if let Some(button) = input.last_pad_pressed() {
+            input.bindings.bind_button(action, button)
+        } else {
+            return;
+        };
+        *blocked = result.err();
+        *rebinding = false;
+        return;
+    }
+    
+    // Un lugar por tecla y uno más, libre, para agregar otra
+    let slots = (input.bindings.get(action).keys.len() + 1).min(MAX_KEYS);
+    if input.pressed(Action::MenuUp) {
+        *row = row.saturating_sub(1);
+    } else if input.pressed(Action::MenuDown) {
+        *row = (*row + 1).min(Action::ALL.len() - 1);
+    } else if input.pressed(Action::MenuLeft) {
+        *slot = slot.saturating_sub(1);
+    } else if input.pressed(Action::MenuRight) {
+        *slot = (*slot + 1).min(slots - 1);
+    } else if input.pressed(Action::Interact) {
+        *rebinding = true;
+        *blocked = None;
+    } else if input.pressed(Action::ResetBindings) {
+        input.bindings = Bindings::default();
+        *blocked = None;
+    } else if input.pressed(Action::Pause) {
+        match input.bindings.save(BINDINGS_FILE) {
+            Ok(()) => println!("✓ Controles guardados en {}", BINDINGS_FILE),
+            Err(e) => println!("✗ No se pudieron guardar los controles en {}: {}", BINDINGS_FILE, e),
+        }
+        game_state.current_screen = Screen::Options;
+    }
+    
+    // Al cambiar de fila el lugar elegido puede quedar fuera de las teclas de la acción
+    let slots = (input.bindings.get(Action::ALL[*row]).keys.len() + 1).min(MAX_KEYS);
+    *slot = (*slot).min(slots - 1);
+}
+
+fn draw_controls(bindings: &Bindings, (selected, selected_slot): (usize, usize), rebinding: bool, blocked: Option<Action>) {
+    clear_background(BLACK);
+    
+    let title = "CONTROLS";
+    let title_size = 40.0;
+    let title_width = measure_text(title, None, title_size as u16, 1.0).width;
+    draw_text(title, (SCREEN_WIDTH - title_width) / 2.0, 60.0, title_size, YELLOW);
+    
+    for (row, action) in Action::ALL.into_iter().enumerate() {
+        let y = 95.0 + row as f32 * 23.0;
+        let color = if row == selected { YELLOW } else { GRAY };
+        if row == selected {
+            draw_text(">", 100.0, y, 20.0, YELLOW);
+        }
+        draw_text(action.name(), 125.0, y, 20.0, color);
+        
+        // Cada tecla por separado, con la elegida resaltada, y después el botón del control
+        let binding = bindings.get(action);
+        let mut x = 340.0;
+        for slot in 0..(binding.keys.len() + 1).min(MAX_KEYS) {
+            let chosen = row == selected && slot == selected_slot;
+            let text = match binding.key_text(slot) {
+                _ if chosen && rebinding => "press a key or button...",
+                Some(name) => name,
+                // El lugar libre solo se muestra en la fila elegida
+                None if row == selected => "+",
+                None => continue,
+            };
+            let slot_color = if chosen { WHITE } else { color };
+            let text = if chosen { format!("[{}]", text) } else { String::from(text) };
+            draw_text(&text, x, y, 20.0, slot_color);
+            x += measure_text(&text, None, 20, 1.0).width + 12.0;
+        }
+        if let Some(button) = binding.button {
+            draw_text(&button.name(), x.max(620.0), y, 20.0, color);
+        }
+    }
+    
+    if let Some(action) = blocked {
+        let notice = format!("{} needs a control: pick a slot to swap instead", action.name());
+        let notice_width = measure_text(&notice, None, 18, 1.0).width;
+        draw_text(&notice, (SCREEN_WIDTH - notice_width) / 2.0, 545.0, 18.0, Color::from_rgba(255, 100, 100, 255));
+    }
+    
+    let hint = format!(
+        "{}/{}: action - {}/{}: slot - {}: rebind - {}: defaults - {}: back/cancel",
+        key_hint(bindings, Action::MenuUp),
+        key_hint(bindings, Action::MenuDown),
+        key_hint(bindings, Action::MenuLeft),
+        key_hint(bindings, Action::MenuRight),
+        key_hint(bindings, Action::Interact),
+        key_hint(bindings, Action::ResetBindings),
+        key_hint(bindings, Action::Pause),
+    );
+    let hint_size = 16.0;
+    let hint_width = measure_text(&hint, None, hint_size as u16, 1.0).width;
+    draw_text(&hint, (SCREEN_WIDTH - hint_width) / 2.0, 575.0, hint_size, GRAY);
+}
+
+// Tecla principal de la acción en mayúsculas, para los textos de ayuda
+fn key_hint(bindings: &Bindings, action: Action) -> String {
+    bindings.get(action).primary().to_uppercase()
+}
+
+fn draw_paused(bindings: &Bindings) {
+    draw_rectangle(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::from_rgba(0, 0, 0, 160));
+    
+    let text = "PAUSED";
+    let size = 50.0;
+    let width = measure_text(text, None, size as u16, 1.0).width;
+    draw_text(text, (SCREEN_WIDTH - width) / 2.0, SCREEN_HEIGHT / 2.0, size, YELLOW);
+    
+    let hint = format!("{}: resume", key_hint(bindings, Action::Pause));
+    let hint_width = measure_text(&hint, None, 20, 1.0).width;
+    draw_text(&hint, (SCREEN_WIDTH - hint_width) / 2.0, SCREEN_HEIGHT / 2.0 + 40.0, 20.0, GRAY);
+}
+
+fn draw_options(settings: &Settings, selected: usize, bindings: &Bindings) {
+    clear_background(BLACK);
+    
+    let title = "OPTIONS";
+    let title_size = 50.0;
+    let title_width = measure_text(title, None, title_size as u16, 1.0).width;
+    draw_text(title, (SCREEN_WIDTH - title_width) / 2.0, 150.0, title_size, YELLOW);
+    
+    for (row, option) in SettingsOption::ALL.into_iter().enumerate() {
+        let y = 230.0 + row as f32 * 40.0;
+        let color = if row == selected { YELLOW } else { GRAY };
+        if row == selected {
+            draw_text(">", 200.0, y, 26.0, YELLOW);
+        }
+        draw_text(option.name(), 230.0, y, 26.0, color);
+        draw_text(&format!("< {} >", settings.value_text(option)), 500.0, y, 26.0, color);
     }
+    
+    let controls_row = SettingsOption::ALL.len();
+    let y = 230.0 + controls_row as f32 * 40.0;
+    let color = if selected == controls_row { YELLOW } else { GRAY };
+    if selected == controls_row {
+        draw_text(">", 200.0, y, 26.0, YELLOW);
+    }
+    draw_text("Controls...", 230.0, y, 26.0, color);
+    
+    let hint = format!(
+        "{}/{}: choose - {}/{}: change - {}: back",
+        key_hint(bindings, Action::MenuUp),
+        key_hint(bindings, Action::MenuDown),
+        key_hint(bindings, Action::MenuLeft),
+        key_hint(bindings, Action::MenuRight),
+        key_hint(bindings, Action::Pause),
+    );
+    let hint_size = 20.0;
+    let hint_width = measure_text(&hint, None, hint_size as u16, 1.0).width;
+    draw_text(&hint, (SCREEN_WIDTH - hint_width) / 2.0, 520.0, hint_size, GRAY);
 }
 
 // Menu Inicial
-fn draw_menu(_texture_manager: &TextureManager) {
+fn draw_menu(_texture_manager: &TextureManager, difficulty: Difficulty, bindings: &Bindings) {
     clear_background(BLACK);
     
     let title = "BACKROOMS";
@@ -728,22 +1192,75 @@ fn draw_menu(_texture_manager: &TextureManager) {
         DARKGRAY,
     );
     
-    let instruction = "Press SPACE to Start";
+    let instruction = format!(
+        "Press {} to Start - {} for Endless",
+        key_hint(bindings, Action::Interact),
+        key_hint(bindings, Action::Endless),
+    );
     let instruction_size = 25.0;
-    let instruction_width = measure_text(instruction, None, instruction_size as u16, 1.0).width;
+    let instruction_width = measure_text(&instruction, None, instruction_size as u16, 1.0).width;
     draw_text(
-        instruction,
+        &instruction,
         (SCREEN_WIDTH - instruction_width) / 2.0,
         400.0,
         instruction_size,
         WHITE,
     );
     
-    let controls = "Use WASD to move, Mouse to look around";
+    // Dificultad elegida y lo que cambia
+    let settings = difficulty.settings();
+    let difficulty_text = format!("< Difficulty: {} >", difficulty.name());
+    let difficulty_size = 22.0;
+    let difficulty_width = measure_text(&difficulty_text, None, difficulty_size as u16, 1.0).width;
+    draw_text(
+        &difficulty_text,
+        (SCREEN_WIDTH - difficulty_width) / 2.0,
+        320.0,
+        difficulty_size,
+        YELLOW,
+    );
+    
+    let details = format!(
+        "Enemy speed x{:.2} - Map size x{:.1} - Stamina {:.0}s - Minimap: {}",
+        settings.enemy_speed,
+        settings.map_size,
+        settings.stamina,
+        if settings.minimap { "yes" } else { "no" },
+    );
+    let details_size = 16.0;
+    let details_width = measure_text(&details, None, details_size as u16, 1.0).width;
+    draw_text(
+        &details,
+        (SCREEN_WIDTH - details_width) / 2.0,
+        345.0,
+        details_size,
+        GRAY,
+    );
+    
+    let options_text = format!("{}: Options (camera, mouse, controls)", key_hint(bindings, Action::Options));
+    let options_size = 18.0;
+    let options_width = measure_text(&options_text, None, options_size as u16, 1.0).width;
+    draw_text(
+        &options_text,
+        (SCREEN_WIDTH - options_width) / 2.0,
+        372.0,
+        options_size,
+        GRAY,
+    );
+    
+    let controls = format!(
+        "Use {}{}{}{} to move, Mouse to look around - {}/{}: difficulty",
+        key_hint(bindings, Action::MoveForward),
+        key_hint(bindings, Action::StrafeLeft),
+        key_hint(bindings, Action::MoveBackward),
+        key_hint(bindings, Action::StrafeRight),
+        key_hint(bindings, Action::MenuLeft),
+        key_hint(bindings, Action::MenuRight),
+    );
     let controls_size = 20.0;
-    let controls_width = measure_text(controls, None, controls_size as u16, 1.0).width;
+    let controls_width = measure_text(&controls, None, controls_size as u16, 1.0).width;
     draw_text(
-        controls,
+        &controls,
         (SCREEN_WIDTH - controls_width) / 2.0,
         450.0,
         controls_size,
@@ -762,60 +1279,117 @@ fn draw_menu(_texture_manager: &TextureManager) {
     );
 }
 
-async fn update_game(
+// Activar los enemigos del nivel si es momento, y reintentar los que no encontraron lugar
+fn spawn_enemies(enemies: &mut Vec<Enemy>, enemy_textures: &HashMap<&str, Texture2D>, player: &Player, game_state: &mut GameState, dt: f32) {
+    if !game_state.enemy_should_activate {
+        return;
+    }
+    
+    if !enemies.is_empty() {
+        let (map, spawn_points, rng) = game_state.spawn_context();
+        for enemy in enemies.iter_mut() {
+            enemy.retry_spawn(dt, player, map, spawn_points, rng);
+        }
+        return;
+    }
+    
+    let roster = game_state.enemy_roster();
+    let speed_scale = game_state.level_def().enemy.speed_scale * game_state.difficulty.settings().enemy_speed;
+    let (map, spawn_points, rng) = game_state.spawn_context();
+    for archetype in roster {
+        let mut enemy = Enemy::new(archetype, enemy_textures.get(archetype.sprite).cloned());
+        enemy.speed *= speed_scale;
+        enemy.activate(player, map, spawn_points, rng);
+        enemies.push(enemy);
+    }
+}
+
+async fn update_game<'a>(
     player: &mut Player, 
-    enemy: &mut Enemy,
+    enemies: &mut Vec<Enemy>,
     game_state: &mut GameState, 
-    sounds: &HashMap<&str, Sound>,
+    sounds: &HashMap<&'a str, Sound>,
+    renderer: &Renderer,
     footstep_playing: &mut bool,
-    enemy_sound_playing: &mut bool
+    enemy_sound: &mut Option<&'a str>
 ) {
     let dt = get_frame_time();
 
-    player.update(dt, &game_state.world_map);
-
-    // Activar enemigo si es momento
-    if game_state.enemy_should_activate && !enemy.active {
-        enemy.activate(player, &game_state.world_map);
+    game_state.update_world(player.x, player.y);
+    let flashlight_was_on = player.flashlight.on;
+    player.update(dt, game_state.map(), &game_state.input, &game_state.settings);
+    if player.flashlight.on != flashlight_was_on {
+        if let Some(click) = sounds.get("flashlight_click") {
+            play_sound_once(click);
+        }
+    }
+    
+    // Los ruidos del jugador se propagan por el mapa y alertan a los enemigos a los que les llegan
+    for noise in player.take_noises() {
+        if !enemies.is_empty() {
+            let field = NoiseField::propagate(game_state.map(), &noise);
+            for enemy in enemies.iter_mut() {
+                enemy.hear(&noise, &field);
+            }
+        }
     }
 
-    // Actualizar enemigo
-    if enemy.active {
-        enemy.update(dt, player, &game_state.world_map);
+    // Actualizar enemigos
+    for enemy in enemies.iter_mut() {
+        // Visto en el último cuadro dibujado: el Stalker no se mueve mientras lo miran
+        let observed = enemy.sprite(player).is_some_and(|sprite| renderer.is_visible(&sprite, player));
+        enemy.update(dt, player, game_state.map(), observed);
         
-        // Verificar si el enemigo atrapó al jugador
-        if enemy.check_player_collision(player) && !game_state.game_over {
-            game_state.trigger_death();
-            
-            // Reproducir sonido del screamer de muerte
-            if !game_state.death_screamer_sound_played {
-                if let Some(death_scream) = sounds.get("scream3") {
-                    play_sound_once(death_scream);
-                    println!("¡ENEMIGO TE ATRAPÓ! SCREAMER DE MUERTE ACTIVADO!");
+        // Reaccionar a los cambios de estado de la IA
+        for event in enemy.take_events() {
+            match event.to {
+                AiState::Chase => {
+                    if let Some(alert) = sounds.get("enemy_alert") {
+                        play_sound_once(alert);
+                    }
+                    game_state.enemy_alert_timer = ENEMY_ALERT_TIME;
+                    game_state.director.on_scare();
+                    println!("¡{} te vio!", enemy.archetype.name);
                 }
-                game_state.death_screamer_sound_played = true;
+                AiState::Wander if event.gave_up() => println!("{} dejó de buscarte", enemy.archetype.name),
+                _ => {}
             }
-            
-            // Detener sonidos del enemigo
-            if *enemy_sound_playing {
-                if let Some(enemy_bg) = sounds.get("enemigoBackground") {
-                    stop_sound(enemy_bg);
-                }
-                *enemy_sound_playing = false;
+        }
+    }
+    
+    // Verificar si algún enemigo atrapó al jugador
+    if enemies.iter().any(|enemy| enemy.check_player_collision(player)) && !game_state.game_over {
+        game_state.trigger_death();
+        
+        // Reproducir sonido del screamer de muerte
+        if !game_state.death_screamer_sound_played {
+            if let Some(death_scream) = sounds.get("scream3") {
+                play_sound_once(death_scream);
+                println!("¡ENEMIGO TE ATRAPÓ! SCREAMER DE MUERTE ACTIVADO!");
             }
-            
-            enemy.deactivate();
-            return;
+            game_state.death_screamer_sound_played = true;
         }
         
-        // Controlar sonido del enemigo basado en distancia
-        let distance_to_player = enemy.get_distance_to_player(player);
-        if distance_to_player < 15.0 { // Solo reproducir si está relativamente cerca
-            if let Some(enemy_bg) = sounds.get("enemigoBackground") {
-                // Calcular volumen basado en distancia
-                let volume = (1.0 - (distance_to_player / 15.0)).max(0.1).min(0.8);
-                
-                if !*enemy_sound_playing {
+        // Detener sonidos del enemigo
+        stop_enemy_sound(sounds, enemy_sound);
+        
+        enemies.clear();
+        return;
+    }
+    
+    // Controlar sonido de enemigo según el más cercano: cada tipo tiene su propio bucle
+    let nearest = enemies
+        .iter()
+        .map(|enemy| (enemy, enemy.get_distance_to_player(player)))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    match nearest {
+        Some((enemy, distance_to_player)) if distance_to_player < 15.0 => { // Solo reproducir si está relativamente cerca
+            let name = enemy_sound_name(sounds, enemy.archetype.sound);
+            if *enemy_sound != Some(name) {
+                stop_enemy_sound(sounds, enemy_sound);
+                if let Some(enemy_bg) = sounds.get(name) {
+                    // Calcular volumen basado en distancia
+                    let volume = (1.0 - (distance_to_player / 15.0)).clamp(0.1, 0.8);
                     play_sound(
                         enemy_bg,
                         PlaySoundParams {
@@ -823,22 +1397,18 @@ async fn update_game(
                             volume,
                         },
                     );
-                    *enemy_sound_playing = true;
+                    *enemy_sound = Some(name);
                     println!("Sonido de enemigo iniciado (distancia: {:.1})", distance_to_player);
                 }
-       
-            }
-        } else {
-            // Detener sonido si está muy lejos
-            if *enemy_sound_playing {
-                if let Some(enemy_bg) = sounds.get("enemigoBackground") {
-                    stop_sound(enemy_bg);
-                }
-                *enemy_sound_playing = false;
             }
         }
+        // Detener sonido si están todos lejos
+        _ => stop_enemy_sound(sounds, enemy_sound),
     }
 
+    // La linterna gasta batería y parpadea con un enemigo cerca
+    player.flashlight.update(dt, nearest.map(|(_, distance)| distance));
+
     // Verificar si el screamer de salida debe activarse
     if game_state.check_screamer_distance(player.x, player.y) {
         // Reproducir sonido del screamer
@@ -848,8 +1418,12 @@ async fn update_game(
         }
     }
     
-    // Verificar si el screamer aleatorio debe activarse
-    if game_state.check_random_screamer() {
+    // El director decide cuándo aparecen los enemigos y cuándo hay un susto, según la tensión
+    let tension = Tension {
+        nearest_enemy: nearest.map(|(_, distance)| distance),
+        chased: enemies.iter().any(|enemy| enemy.state() == AiState::Chase),
+    };
+    if game_state.update_director(dt, &tension) {
         // Reproducir sonido del screamer2
         if let Some(scream2_sound) = sounds.get("screamer2") {
             play_sound_once(scream2_sound);
@@ -874,37 +1448,105 @@ async fn update_game(
         }
     }
     
-    // Verificar victoria - ACTUALIZADO para mapa 40x30
-    let player_map_x = player.x as usize;
-    let player_map_y = player.y as usize;
-    
-    if player_map_x < 40 && player_map_y < 30 && game_state.world_map[player_map_y][player_map_x] == 3 {
-        game_state.escaped = true;
+    // Disparadores de la celda que pisa el jugador
+    if let Some(tile) = game_state.map().tile_at(player.x, player.y) {
+        match tile.props().trigger {
+            TileTrigger::Exit => {
+                if game_state.reach_exit() {
+                    // Nuevo nivel: el jugador empieza en su inicio y el enemigo vuelve a esperar
+                    player.respawn(game_state.player_start);
+                    enemies.clear();
+                    stop_enemy_sound(sounds, enemy_sound);
+                }
+            }
+            TileTrigger::Battery => {
+                player.pick_up_battery(BATTERY_PICKUP_CHARGE);
+                game_state.take_pickup(player.x.floor() as i32, player.y.floor() as i32);
+                if let Some(pickup) = sounds.get("battery_pickup") {
+                    play_sound_once(pickup);
+                }
+                println!("Batería recogida ({:.0}%)", player.flashlight.battery * 100.0);
+            }
+            TileTrigger::None => {}
+        }
     }
 }
 
-fn draw_game(player: &Player, enemy: &Enemy, game_state: &GameState, texture_manager: &TextureManager, minimap: &Minimap) {
+fn draw_game(
+    player: &Player,
+    enemies: &[Enemy],
+    game_state: &GameState,
+    texture_manager: &TextureManager,
+    minimap: &Minimap,
+    renderer: &mut Renderer,
+) {
     clear_background(Color::from_rgba(20, 20, 10, 255));
     
     // Raycasting
-    render_world(player, game_state, texture_manager);
-    
-    // Renderizar enemigo en el mundo 3D con oclusión
-    enemy.render_in_world(player, SCREEN_WIDTH, SCREEN_HEIGHT, &game_state.world_map);
+    let level = game_state.level_def();
+    renderer.render_world(player, game_state.map(), texture_manager, level, &game_state.light_map, player.flashlight.intensity());
+    
+    // Sprites del mundo recortados contra las paredes, con la luz del nivel, las lámparas y la linterna
+    let mut sprites = tile_sprites(game_state.map(), player, texture_manager);
+    sprites.extend(enemies.iter().filter_map(|enemy| enemy.sprite(player)));
+    let light = level.palette.light;
+    for sprite in &mut sprites {
+        sprite.tint = Color::new(sprite.tint.r * light[0], sprite.tint.g * light[1], sprite.tint.b * light[2], sprite.tint.a);
+    }
+    renderer.draw_sprites(&mut sprites, player, &game_state.light_map);
     
-    // Minimapa - CORREGIDO: usar método draw en lugar de draw_with_enemy
-    minimap.draw_with_enemy(player, enemy, &game_state.world_map);
+    // Minimapa con todos los enemigos, si la dificultad lo permite
+    if game_state.difficulty.settings().minimap {
+        minimap.draw_with_enemies(player, enemies, game_state.map());
+    }
+    if minimap.debug {
+        let director = &game_state.director;
+        let text = format!("Tension: {:.2} ({:?})", director.stress(), director.phase());
+        draw_text(&text, SCREEN_WIDTH - 200.0, SCREEN_HEIGHT - 20.0, 14.0, Color::from_rgba(80, 200, 255, 220));
+    }
     
     // HUD
-    draw_hud();
+    draw_hud(&game_state.input.bindings);
+    draw_stamina_bar(&player.stamina);
+    draw_battery_bar(&player.flashlight);
+    
+    if game_state.level_transition_timer > 0.0 {
+        draw_level_transition(game_state.level_def().name, game_state.level_transition_timer);
+    }
+    
+    if game_state.enemy_alert_timer > 0.0 {
+        draw_enemy_alert(game_state.enemy_alert_timer);
+    }
     
-    // Indicador de peligro si el enemigo está cerca
-    if enemy.active {
-        let distance = enemy.get_distance_to_player(player);
-        if distance < 5.0 {
-            draw_danger_indicator(distance);
+    // Indicador de peligro según el enemigo más cercano
+    let nearest = enemies.iter().map(|enemy| enemy.get_distance_to_player(player)).fold(f32::MAX, f32::min);
+    if nearest < 5.0 {
+        draw_danger_indicator(nearest);
+    }
+}
+
+// Objetos de las celdas cercanas al jugador (baterías), apoyados en el centro de su celda
+fn tile_sprites<'a>(map: &dyn TileMap, player: &Player, texture_manager: &'a TextureManager) -> Vec<Sprite<'a>> {
+    let (player_x, player_y) = (player.x.floor() as i32, player.y.floor() as i32);
+    let mut sprites = Vec::new();
+    for y in player_y - TILE_SPRITE_RANGE..=player_y + TILE_SPRITE_RANGE {
+        for x in player_x - TILE_SPRITE_RANGE..=player_x + TILE_SPRITE_RANGE {
+            let texture = map
+                .tile(x, y)
+                .and_then(|tile| tile.props().sprite)
+                .and_then(|name| texture_manager.textures.get(name));
+            if let Some(texture) = texture {
+                sprites.push(Sprite {
+                    x: x as f32 + 0.5,
+                    y: y as f32 + 0.5,
+                    texture,
+                    height: TILE_SPRITE_SIZE,
+                    tint: WHITE,
+                });
+            }
         }
     }
+    sprites
 }
 
 fn draw_danger_indicator(distance: f32) {
@@ -939,104 +1581,40 @@ fn draw_danger_indicator(distance: f32) {
     }
 }
 
-fn render_world(player: &Player, game_state: &GameState, _texture_manager: &TextureManager) {
-    let fov = std::f32::consts::PI / 3.0; 
-    let half_fov = fov / 2.0;
-    let num_rays = SCREEN_WIDTH as usize;
-    let delta_angle = fov / num_rays as f32;
-    
-    for i in 0..num_rays {
-        let angle = player.angle - half_fov + i as f32 * delta_angle;
-        let (distance, wall_type, hit_vertical) = cast_ray(player, angle, &game_state.world_map);
-        
-        if distance > 0.0 {
-            draw_wall_slice(i, distance, wall_type, hit_vertical, angle - player.angle);
-        }
-    }
-}
-
-fn cast_ray(player: &Player, angle: f32, world_map: &[[u8; 40]; 30]) -> (f32, u8, bool) {
-    let dx = angle.cos();
-    let dy = angle.sin();
-    let mut x = player.x;
-    let mut y = player.y;
-    let step_size = 0.02;
-    
-    loop {
-        x += dx * step_size;
-        y += dy * step_size;
-        
-        let map_x = x as usize;
-        let map_y = y as usize;
-        
-        // ACTUALIZADO para mapa 40x30
-        if map_y >= 30 || map_x >= 40 {
-            return (1000.0, 1, false); 
-        }
-        
-        let wall_type = world_map[map_y][map_x];
-        if wall_type != 0 {
-            let distance = ((x - player.x).powi(2) + (y - player.y).powi(2)).sqrt();
-            let hit_vertical = x.fract() < 0.1 || x.fract() > 0.9;
-            return (distance, wall_type, hit_vertical);
-        }
-    }
+// Signo de alerta que se desvanece cuando el enemigo empieza a perseguir
+fn draw_enemy_alert(time_left: f32) {
+    let alpha = (time_left / ENEMY_ALERT_TIME).min(1.0);
+    let text_size = 48.0;
+    let text_width = measure_text("!", None, text_size as u16, 1.0).width;
+    draw_text(
+        "!",
+        (SCREEN_WIDTH - text_width) / 2.0,
+        SCREEN_HEIGHT / 2.0 - 40.0,
+        text_size,
+        Color::new(1.0, 0.2, 0.2, alpha),
+    );
 }
 
-fn draw_wall_slice(x: usize, distance: f32, wall_type: u8, hit_vertical: bool, ray_angle: f32) {
-    let corrected_distance = distance * ray_angle.cos();
-    
-    let wall_height = (SCREEN_HEIGHT / (corrected_distance + 0.0001)) * 0.6;
-    let wall_top = (SCREEN_HEIGHT - wall_height) / 2.0;
-    let wall_bottom = wall_top + wall_height;
-    
-    // Colores de las paredes mejorados
-    let mut color = match wall_type {
-        1 => Color::from_rgba(160, 160, 100, 255),
-        2 => Color::from_rgba(120, 60, 40, 255),   
-        3 => Color::from_rgba(40, 180, 40, 255),   
-        _ => GRAY,
-    };
-    
-    // Oscurecer paredes verticales para dar profundidad
-    if hit_vertical {
-        color.r *= 0.8;
-        color.g *= 0.8;
-        color.b *= 0.8;
-    }
-    
-    // Oscurecer basado en distancia para mejor atmósfera
-    let brightness = (1.0 - (corrected_distance / 12.0).min(0.7)).max(0.3);
-    color.r *= brightness;
-    color.g *= brightness;
-    color.b *= brightness;
-    
-    // Dibujar pared
-    draw_line(x as f32, wall_top, x as f32, wall_bottom, 1.0, color);
-    
-    // Dibujar suelo y techo con gradiente
-    if wall_top > 0.0 {
-        let ceiling_color = Color::from_rgba(
-            (30.0 * brightness) as u8, 
-            (25.0 * brightness) as u8, 
-            (15.0 * brightness) as u8, 
-            255
-        );
-        draw_line(x as f32, 0.0, x as f32, wall_top, 1.0, ceiling_color);
-    }
-    if wall_bottom < SCREEN_HEIGHT {
-        let floor_color = Color::from_rgba(
-            (50.0 * brightness) as u8, 
-            (40.0 * brightness) as u8, 
-            (20.0 * brightness) as u8, 
-            255
-        );
-        draw_line(x as f32, wall_bottom, x as f32, SCREEN_HEIGHT, 1.0, floor_color);
-    }
+// Fundido desde negro con el nombre del nivel al que se acaba de entrar
+fn draw_level_transition(level_name: &str, time_left: f32) {
+    let progress = time_left / LEVEL_TRANSITION_TIME;
+    // Negro total durante el primer tercio y luego se aclara
+    let alpha = (progress * 1.5).min(1.0);
+    draw_rectangle(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::new(0.0, 0.0, 0.0, alpha));
+    
+    let text_size = 32.0;
+    let text_width = measure_text(level_name, None, text_size as u16, 1.0).width;
+    draw_text(
+        level_name,
+        (SCREEN_WIDTH - text_width) / 2.0,
+        SCREEN_HEIGHT / 2.0,
+        text_size,
+        Color::new(0.9, 0.85, 0.6, progress.min(1.0)),
+    );
 }
 
 // HUD simulacion de camara
-fn draw_hud() {
+fn draw_hud(bindings: &Bindings) {
     // Efecto de cámara vintage con bordes más sutiles
     draw_rectangle_lines(5.0, 5.0, SCREEN_WIDTH - 10.0, SCREEN_HEIGHT - 10.0, 1.5, Color::from_rgba(200, 50, 50, 180));
     
@@ -1054,7 +1632,52 @@ fn draw_hud() {
     
     // Botón PLAY
     draw_text("PLAY >", 15.0, 25.0, 18.0, Color::from_rgba(200, 200, 200, 180));
-    draw_text("WASD: Move | Mouse: Look", 15.0, SCREEN_HEIGHT - 20.0, 12.0, Color::from_rgba(150, 150, 150, 120));
+    let key = |action| key_hint(bindings, action);
+    let controls = format!(
+        "{}{}{}{}: Move | Mouse: Look | {}: Run | {}: Crouch | {}: Flashlight | {}: Pause",
+        key(Action::MoveForward),
+        key(Action::StrafeLeft),
+        key(Action::MoveBackward),
+        key(Action::StrafeRight),
+        key(Action::Sprint),
+        key(Action::Crouch),
+        key(Action::Flashlight),
+        key(Action::Pause),
+    );
+    draw_text(&controls, 15.0, SCREEN_HEIGHT - 20.0, 12.0, Color::from_rgba(150, 150, 150, 120));
+}
+
+// Barra de stamina discreta abajo a la derecha, con los colores del HUD de cámara; parpadea si está agotado
+fn draw_stamina_bar(stamina: &Stamina) {
+    let (x, y, width, height) = (SCREEN_WIDTH - 130.0, SCREEN_HEIGHT - 48.0, 100.0, 5.0);
+    let full = stamina.fraction() >= 1.0;
+    let alpha = if stamina.exhausted {
+        (get_time() * 6.0).sin() as f32 * 0.3 + 0.6
+    } else if full {
+        0.3
+    } else {
+        0.7
+    };
+    
+    draw_text("STM", x - 30.0, y + 6.0, 14.0, Color::new(1.0, 0.4, 0.4, alpha));
+    draw_rectangle_lines(x, y, width, height, 1.0, Color::new(1.0, 0.4, 0.4, alpha));
+    draw_rectangle(x, y, width * stamina.fraction(), height, Color::new(1.0, 0.4, 0.4, alpha * 0.8));
+}
+
+// Carga de la linterna sobre la barra de stamina, con el mismo estilo; parpadea con poca batería
+fn draw_battery_bar(flashlight: &Flashlight) {
+    let (x, y, width, height) = (SCREEN_WIDTH - 130.0, SCREEN_HEIGHT - 62.0, 100.0, 5.0);
+    let alpha = if flashlight.battery < 0.2 {
+        (get_time() * 4.0).sin() as f32 * 0.3 + 0.6
+    } else if flashlight.on {
+        0.7
+    } else {
+        0.35
+    };
+    
+    draw_text("BAT", x - 30.0, y + 6.0, 14.0, Color::new(1.0, 0.4, 0.4, alpha));
+    draw_rectangle_lines(x, y, width, height, 1.0, Color::new(1.0, 0.4, 0.4, alpha));
+    draw_rectangle(x, y, width * flashlight.battery, height, Color::new(1.0, 0.4, 0.4, alpha * 0.8));
 }
 
 async fn handle_victory(game_state: &mut GameState, sounds: &HashMap<&str, Sound>) {
@@ -1066,14 +1689,15 @@ async fn handle_victory(game_state: &mut GameState, sounds: &HashMap<&str, Sound
             play_sound_once(victory);
         }
         game_state.victory_sound_played = true;
+        game_state.record_result(true);
     }
     
-    if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Space) {
+    if game_state.input.pressed(Action::Interact) || game_state.input.pressed(Action::Pause) {
         game_state.reset();
     }
 }
 
-fn draw_victory() {
+fn draw_victory(seed: u64, difficulty: Difficulty, bindings: &Bindings) {
     clear_background(Color::from_rgba(10, 40, 10, 255));
     
     let victory_text = "¡FELICIDADES!";
@@ -1098,14 +1722,43 @@ fn draw_victory() {
         WHITE,
     );
     
-    let instruction = "Press ESC or SPACE to play again";
+    let instruction = format!(
+        "Press {} or {} to play again",
+        key_hint(bindings, Action::Pause),
+        key_hint(bindings, Action::Interact),
+    );
     let instruction_size = 18.0;
-    let instruction_width = measure_text(instruction, None, instruction_size as u16, 1.0).width;
+    let instruction_width = measure_text(&instruction, None, instruction_size as u16, 1.0).width;
     draw_text(
-        instruction,
+        &instruction,
         (SCREEN_WIDTH - instruction_width) / 2.0,
         350.0,
         instruction_size,
         GRAY,
     );
-}
\ No newline at end of file
+    
+    draw_run_info(seed, difficulty, 400.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemy_archetypes::SHADOW;
+
+    #[test]
+    fn a_new_run_starts_without_the_previous_enemies() {
+        let mut game_state = GameState::new(Some(1));
+        let mut player = Player::new(1.5, 1.5, 0.0);
+        let mut enemies = vec![Enemy::new(&SHADOW, None), Enemy::new(&SHADOW, None)];
+        let sounds = HashMap::new();
+        let mut enemy_sound = None;
+
+        begin_run(&mut game_state, &mut player, &mut enemies, (&sounds, &mut enemy_sound), GameState::start_game);
+        assert!(enemies.is_empty());
+        assert!(game_state.current_screen == Screen::Game);
+
+        enemies.push(Enemy::new(&SHADOW, None));
+        begin_run(&mut game_state, &mut player, &mut enemies, (&sounds, &mut enemy_sound), GameState::start_endless);
+        assert!(enemies.is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+// Generadores de trazado. Cada uno recibe un mapa lleno de paredes y abre los
+// espacios transitables; quien lo usa (GameState o el mundo por chunks) agrega
+// después la sangre, la salida y el inicio, y valida/repara la conectividad.
+
+use rand::{Rng, rngs::StdRng, seq::SliceRandom};
+use crate::campaign::GeneratorParams;
+use crate::lighting::{CeilingLight, LightKind};
+use crate::tile::Tile;
+use crate::world_map::WorldMap;
+
+pub trait MapGenerator {
+    /// Abre los espacios del trazado sin tocar el borde del mapa
+    fn carve(&self, world_map: &mut WorldMap, params: &GeneratorParams, rng: &mut StdRng);
+}
+
+/// Generadores disponibles, para elegirlos desde la campaña o la línea de comandos
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GeneratorKind {
+    Maze,
+    Rooms,
+    Caves,
+    Office,
+}
+
+impl GeneratorKind {
+    pub const ALL: [GeneratorKind; 4] = [
+        GeneratorKind::Maze,
+        GeneratorKind::Rooms,
+        GeneratorKind::Caves,
+        GeneratorKind::Office,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GeneratorKind::Maze => "maze",
+            GeneratorKind::Rooms => "rooms",
+            GeneratorKind::Caves => "caves",
+            GeneratorKind::Office => "office",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GeneratorKind> {
+        GeneratorKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn generator(self) -> &'static dyn MapGenerator {
+        match self {
+            GeneratorKind::Maze => &MazeGenerator,
+            GeneratorKind::Rooms => &RoomsGenerator,
+            GeneratorKind::Caves => &CavesGenerator,
+            GeneratorKind::Office => &OfficeGenerator,
+        }
+    }
+}
+
+fn open(world_map: &mut WorldMap, x: usize, y: usize) {
+    world_map.set(x, y, Tile::Empty);
+}
+
+// Pasillo en L entre dos celdas interiores, primero horizontal o vertical al azar
+fn carve_l_corridor(world_map: &mut WorldMap, from: (usize, usize), to: (usize, usize), rng: &mut StdRng) {
+    let corner = if rng.gen_bool(0.5) { (to.0, from.1) } else { (from.0, to.1) };
+
+    for (a, b) in [(from, corner), (corner, to)] {
+        for x in a.0.min(b.0)..=a.0.max(b.0) {
+            for y in a.1.min(b.1)..=a.1.max(b.1) {
+                open(world_map, x, y);
+            }
+        }
+    }
+}
+
+/// Convierte algunas paredes que dan a un espacio abierto en paredes con sangre
+pub fn add_bloody_walls(world_map: &mut WorldMap, chance: f64, rng: &mut StdRng) {
+    for y in 0..world_map.height() {
+        for x in 0..world_map.width() {
+            if world_map.get(x, y) == Some(Tile::Wall) && rng.gen_bool(chance) {
+                // Solo agregar sangre si hay al menos un espacio vacío adyacente
+                let adjacent_empty = [
+                    (x.wrapping_sub(1), y),
+                    (x + 1, y),
+                    (x, y.wrapping_sub(1)),
+                    (x, y + 1),
+                ].iter().any(|&(ax, ay)| world_map.get(ax, ay) == Some(Tile::Empty));
+
+                if adjacent_empty {
+                    world_map.set(x, y, Tile::BloodyWall);
+                }
+            }
+        }
+    }
+}
+
+/// Deja baterías para la linterna en algunas celdas vacías
+pub fn add_batteries(world_map: &mut WorldMap, chance: f64, rng: &mut StdRng) {
+    for y in 0..world_map.height() {
+        for x in 0..world_map.width() {
+            if world_map.get(x, y) == Some(Tile::Empty) && rng.gen_bool(chance) {
+                world_map.set(x, y, Tile::Battery);
+            }
+        }
+    }
+}
+
+/// Cuelga luces del techo: a lo sumo una por cada bloque de light_spacing x light_spacing
+/// celdas, sobre una celda libre del bloque. Algunas salen fallando o quemadas.
+pub fn place_lights(world_map: &WorldMap, params: &GeneratorParams, rng: &mut StdRng) -> Vec<CeilingLight> {
+    let spacing = params.light_spacing.max(1);
+    let mut lights = Vec::new();
+
+    for block_y in (0..world_map.height()).step_by(spacing) {
+        for block_x in (0..world_map.width()).step_by(spacing) {
+            let free: Vec<(usize, usize)> = (block_y..(block_y + spacing).min(world_map.height()))
+                .flat_map(|y| (block_x..(block_x + spacing).min(world_map.width())).map(move |x| (x, y)))
+                .filter(|&(x, y)| world_map.get(x, y).is_some_and(|tile| !tile.blocks_sight() && !tile.is_solid_for_player()))
+                .collect();
+            let Some(&(x, y)) = free.choose(rng) else {
+                continue;
+            };
+
+            let kind = if rng.gen_bool(params.broken_light_chance) {
+                LightKind::Broken
+            } else if rng.gen_bool(params.flickering_light_chance) {
+                LightKind::Flickering
+            } else {
+                LightKind::Steady
+            };
+            lights.push(CeilingLight { x: x as i32, y: y as i32, kind, seed: rng.gen() });
+        }
+    }
+
+    lights
+}
+
+/// Laberinto por backtracking recursivo con pasillos extra y áreas circulares abiertas
+pub struct MazeGenerator;
+
+impl MapGenerator for MazeGenerator {
+    fn carve(&self, world_map: &mut WorldMap, params: &GeneratorParams, rng: &mut StdRng) {
+        let width = world_map.width();
+        let height = world_map.height();
+
+        // Algoritmo mejorado de generación de laberinto para mapas grandes
+        let mut stack = Vec::new();
+        let mut visited = vec![false; width * height];
+
+        let start_x = 1;
+        let start_y = 1;
+
+        stack.push((start_x, start_y));
+        visited[start_y * width + start_x] = true;
+        open(world_map, start_x, start_y);
+
+        while let Some((x, y)) = stack.pop() {
+            let neighbors = unvisited_neighbors(world_map, x, y, &visited);
+
+            if !neighbors.is_empty() {
+                stack.push((x, y));
+
+                let &(nx, ny) = neighbors.choose(rng).unwrap();
+
+                // Carvar el camino hacia el vecino
+                let wall_x = (x + nx) / 2;
+                let wall_y = (y + ny) / 2;
+
+                open(world_map, wall_x, wall_y);
+                open(world_map, nx, ny);
+                visited[ny * width + nx] = true;
+
+                stack.push((nx, ny));
+            }
+        }
+
+        // Crear pasillos adicionales para hacer el laberinto más interesante
+        // (50 en el mapa original de 40x30, proporcional al área)
+        let extra_corridors = (width * height / params.cells_per_extra_corridor).max(1);
+        for _ in 0..extra_corridors {
+            let x = rng.gen_range(1..width - 1);
+            let y = rng.gen_range(1..height - 1);
+            if x % 2 == 1 && y % 2 == 1 {
+                open(world_map, x, y);
+
+                // Conectar con un pasillo vecino ocasionalmente
+                if rng.gen_bool(0.3) {
+                    let directions = [(0, 2), (2, 0), (0, -2), (-2, 0)];
+                    if let Some(&(dx, dy)) = directions.choose(rng) {
+                        let new_x = x as i32 + dx;
+                        let new_y = y as i32 + dy;
+                        if new_x >= 1 && new_x < width as i32 - 1 && new_y >= 1 && new_y < height as i32 - 1 {
+                            let bridge_x = x as i32 + dx / 2;
+                            let bridge_y = y as i32 + dy / 2;
+                            open(world_map, bridge_x as usize, bridge_y as usize);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Crear algunas áreas abiertas para hacer el juego más interesante
+        create_open_areas(world_map, params, rng);
+    }
+}
+
+fn create_open_areas(world_map: &mut WorldMap, params: &GeneratorParams, rng: &mut StdRng) {
+    let width = world_map.width();
+    let height = world_map.height();
+
+    // Crear unas pocas áreas abiertas pequeñas por cada 40x30 celdas de mapa
+    let (min_areas, max_areas) = params.open_areas;
+    let area_scale = (width * height).div_ceil(1200);
+    let num_areas = rng.gen_range(min_areas..max_areas) * area_scale;
+
+    for _ in 0..num_areas {
+        let center_x = rng.gen_range(5..width - 5);
+        let center_y = rng.gen_range(5..height - 5);
+        let size: i32 = rng.gen_range(2..4);
+
+        // Crear área abierta
+        for dy in -size..=size {
+            for dx in -size..=size {
+                let x = (center_x as i32 + dx) as usize;
+                let y = (center_y as i32 + dy) as usize;
+
+                if world_map.in_bounds(x, y) {
+                    // Crear área circular
+                    let distance = (dx * dx + dy * dy) as f32;
+                    if distance <= (size * size) as f32 {
+                        open(world_map, x, y);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn unvisited_neighbors(world_map: &WorldMap, x: usize, y: usize, visited: &[bool]) -> Vec<(usize, usize)> {
+    let width = world_map.width() as i32;
+    let height = world_map.height() as i32;
+    let mut neighbors = Vec::new();
+    let directions = [(0, 2), (2, 0), (0, -2), (-2, 0)];
+
+    for &(dx, dy) in &directions {
+        let new_x = x as i32 + dx;
+        let new_y = y as i32 + dy;
+
+        if new_x >= 1 && new_x < width - 1 && new_y >= 1 && new_y < height - 1 {
+            let nx = new_x as usize;
+            let ny = new_y as usize;
+
+            if !visited[ny * width as usize + nx] {
+                neighbors.push((nx, ny));
+            }
+        }
+    }
+
+    neighbors
+}
+
+/// Partición binaria del espacio: cuartos rectangulares unidos por pasillos
+pub struct RoomsGenerator;
+
+// Lado mínimo de una hoja de la partición; una hoja más chica no se divide
+const MIN_LEAF_SIZE: usize = 8;
+
+#[derive(Clone, Copy)]
+struct Area {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl RoomsGenerator {
+    // Divide el área hasta que sea pequeña, abre un cuarto en cada hoja y une las
+    // dos mitades de cada división. Devuelve el centro de un cuarto del área.
+    fn split(&self, world_map: &mut WorldMap, area: Area, rng: &mut StdRng) -> (usize, usize) {
+        let can_split_x = area.width >= MIN_LEAF_SIZE * 2;
+        let can_split_y = area.height >= MIN_LEAF_SIZE * 2;
+
+        // Cortar a lo largo del lado más largo; a veces se deja una hoja grande sin dividir
+        let split_x = match (can_split_x, can_split_y) {
+            (false, false) => return self.carve_room(world_map, area, rng),
+            _ if area.width < MIN_LEAF_SIZE * 3 && area.height < MIN_LEAF_SIZE * 3 && rng.gen_bool(0.2) => {
+                return self.carve_room(world_map, area, rng);
+            }
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => area.width >= area.height,
+        };
+
+        let (first, second) = if split_x {
+            let cut = rng.gen_range(MIN_LEAF_SIZE..=area.width - MIN_LEAF_SIZE);
+            (
+                Area { width: cut, ..area },
+                Area { x: area.x + cut, width: area.width - cut, ..area },
+            )
+        } else {
+            let cut = rng.gen_range(MIN_LEAF_SIZE..=area.height - MIN_LEAF_SIZE);
+            (
+                Area { height: cut, ..area },
+                Area { y: area.y + cut, height: area.height - cut, ..area },
+            )
+        };
+
+        let a = self.split(world_map, first, rng);
+        let b = self.split(world_map, second, rng);
+        carve_l_corridor(world_map, a, b, rng);
+
+        if rng.gen_bool(0.5) { a } else { b }
+    }
+
+    // Cuarto dentro del área dejando al menos una celda de pared alrededor
+    fn carve_room(&self, world_map: &mut WorldMap, area: Area, rng: &mut StdRng) -> (usize, usize) {
+        let room_width = rng.gen_range(3..=area.width - 2);
+        let room_height = rng.gen_range(3..=area.height - 2);
+        let room_x = area.x + rng.gen_range(1..=area.width - 1 - room_width);
+        let room_y = area.y + rng.gen_range(1..=area.height - 1 - room_height);
+
+        for y in room_y..room_y + room_height {
+            for x in room_x..room_x + room_width {
+                open(world_map, x, y);
+            }
+        }
+
+        (room_x + room_width / 2, room_y + room_height / 2)
+    }
+}
+
+impl MapGenerator for RoomsGenerator {
+    fn carve(&self, world_map: &mut WorldMap, _params: &GeneratorParams, rng: &mut StdRng) {
+        let area = Area {
+            x: 0,
+            y: 0,
+            width: world_map.width(),
+            height: world_map.height(),
+        };
+        self.split(world_map, area, rng);
+    }
+}
+
+/// Cavernas por autómata celular: ruido aleatorio suavizado varias veces
+pub struct CavesGenerator;
+
+// Proporción inicial de paredes y pasadas de suavizado
+const CAVE_WALL_CHANCE: f64 = 0.45;
+const CAVE_SMOOTHING_STEPS: usize = 5;
+
+impl MapGenerator for CavesGenerator {
+    fn carve(&self, world_map: &mut WorldMap, _params: &GeneratorParams, rng: &mut StdRng) {
+        let width = world_map.width();
+        let height = world_map.height();
+        let interior = |x: usize, y: usize| x > 0 && y > 0 && x < width - 1 && y < height - 1;
+
+        let mut walls: Vec<bool> = (0..width * height)
+            .map(|i| !interior(i % width, i / width) || rng.gen_bool(CAVE_WALL_CHANCE))
+            .collect();
+
+        for _ in 0..CAVE_SMOOTHING_STEPS {
+            let previous = walls.clone();
+            for y in 1..height - 1 {
+                for x in 1..width - 1 {
+                    // Paredes en las 8 celdas vecinas
+                    let mut neighbor_walls = 0;
+                    for ny in y - 1..=y + 1 {
+                        for nx in x - 1..=x + 1 {
+                            if (nx, ny) != (x, y) && previous[ny * width + nx] {
+                                neighbor_walls += 1;
+                            }
+                        }
+                    }
+                    walls[y * width + x] = neighbor_walls >= 5 || (previous[y * width + x] && neighbor_walls >= 4);
+                }
+            }
+        }
+
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                if !walls[y * width + x] {
+                    open(world_map, x, y);
+                }
+            }
+        }
+    }
+}
+
+/// Oficina abierta: un gran espacio con columnas en cuadrícula y mamparas sueltas
+pub struct OfficeGenerator;
+
+// Separación de la cuadrícula de columnas
+const PILLAR_SPACING: usize = 4;
+
+impl MapGenerator for OfficeGenerator {
+    fn carve(&self, world_map: &mut WorldMap, _params: &GeneratorParams, rng: &mut StdRng) {
+        let width = world_map.width();
+        let height = world_map.height();
+
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                open(world_map, x, y);
+            }
+        }
+
+        // Columnas en cuadrícula; algunas faltan y otras son dobles
+        for y in (PILLAR_SPACING..height - 2).step_by(PILLAR_SPACING) {
+            for x in (PILLAR_SPACING..width - 2).step_by(PILLAR_SPACING) {
+                if rng.gen_bool(0.15) {
+                    continue;
+                }
+                world_map.set(x, y, Tile::Wall);
+                if rng.gen_bool(0.2) {
+                    world_map.set(x + 1, y, Tile::Wall);
+                    world_map.set(x, y + 1, Tile::Wall);
+                    world_map.set(x + 1, y + 1, Tile::Wall);
+                }
+            }
+        }
+
+        // Mamparas: paredes rectas cortas entre las columnas
+        let partitions = width * height / 40;
+        for _ in 0..partitions {
+            let length = rng.gen_range(3..7);
+            let horizontal = rng.gen_bool(0.5);
+            let (max_x, max_y) = if horizontal {
+                (width - 1 - length, height - 2)
+            } else {
+                (width - 2, height - 1 - length)
+            };
+            let start_x = rng.gen_range(1..max_x);
+            let start_y = rng.gen_range(1..max_y);
+
+            for step in 0..length {
+                let (x, y) = if horizontal { (start_x + step, start_y) } else { (start_x, start_y + step) };
+                world_map.set(x, y, Tile::Wall);
+            }
+        }
+    }
+}
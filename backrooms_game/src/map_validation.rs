@@ -0,0 +1,290 @@
+// Validación de mapas generados: comprueba con flood fill que desde el punto de
+// inicio se pueda llegar a la salida y a todas las áreas abiertas, y repara el
+// mapa abriendo pasillos cuando alguna zona quedó aislada.
+
+use std::collections::VecDeque;
+use crate::tile::Tile;
+use crate::world_map::WorldMap;
+
+pub struct ValidationReport {
+    /// Celdas transitables en todo el mapa
+    pub open_cells: usize,
+    /// Celdas transitables alcanzables desde el inicio
+    pub reachable_cells: usize,
+    /// Pasos del camino más corto del inicio a la salida, si existe
+    pub exit_path_length: Option<usize>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.exit_path_length.is_some() && self.reachable_cells == self.open_cells
+    }
+}
+
+fn is_open(world_map: &WorldMap, x: usize, y: usize) -> bool {
+    world_map.get(x, y).is_some_and(|tile| !tile.is_solid_for_player())
+}
+
+fn neighbors(x: usize, y: usize) -> [(usize, usize); 4] {
+    [
+        (x.wrapping_sub(1), y),
+        (x + 1, y),
+        (x, y.wrapping_sub(1)),
+        (x, y + 1),
+    ]
+}
+
+/// BFS en 4 direcciones por celdas transitables. Devuelve la distancia en pasos
+/// desde el inicio a cada celda (None si no se alcanza), fila por fila.
+pub fn flood_fill(world_map: &WorldMap, start: (usize, usize)) -> Vec<Option<usize>> {
+    let width = world_map.width();
+    let mut distances = vec![None; width * world_map.height()];
+
+    if !is_open(world_map, start.0, start.1) {
+        return distances;
+    }
+
+    let mut queue = VecDeque::new();
+    distances[start.1 * width + start.0] = Some(0);
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[y * width + x].unwrap_or(0);
+        for (nx, ny) in neighbors(x, y) {
+            if is_open(world_map, nx, ny) && distances[ny * width + nx].is_none() {
+                distances[ny * width + nx] = Some(distance + 1);
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    distances
+}
+
+pub fn validate(world_map: &WorldMap, spawn: (usize, usize), exit: (usize, usize)) -> ValidationReport {
+    let width = world_map.width();
+    let distances = flood_fill(world_map, spawn);
+
+    let mut open_cells = 0;
+    let mut reachable_cells = 0;
+    for y in 0..world_map.height() {
+        for x in 0..width {
+            if is_open(world_map, x, y) {
+                open_cells += 1;
+                if distances[y * width + x].is_some() {
+                    reachable_cells += 1;
+                }
+            }
+        }
+    }
+
+    let exit_path_length = if world_map.get(exit.0, exit.1) == Some(Tile::Exit) {
+        distances[exit.1 * width + exit.0]
+    } else {
+        None
+    };
+
+    ValidationReport {
+        open_cells,
+        reachable_cells,
+        exit_path_length,
+    }
+}
+
+/// Conecta cada zona aislada con la zona del inicio abriendo un pasillo en L
+/// entre sus celdas más cercanas. Devuelve cuántos pasillos se abrieron.
+/// Las celdas abiertas del borde se cierran antes, porque los pasillos no lo tocan.
+pub fn connect_regions(world_map: &mut WorldMap, spawn: (usize, usize)) -> usize {
+    let width = world_map.width();
+    let height = world_map.height();
+    let mut corridors = 0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            if on_border && is_open(world_map, x, y) {
+                world_map.set(x, y, Tile::Wall);
+            }
+        }
+    }
+
+    loop {
+        let reachable: Vec<bool> = flood_fill(world_map, spawn).iter().map(Option::is_some).collect();
+        if !reachable.iter().any(|&r| r) {
+            // El inicio está bloqueado: no hay con qué conectar
+            return corridors;
+        }
+
+        let isolated = (0..reachable.len())
+            .map(|i| (i % width, i / width))
+            .find(|&(x, y)| is_open(world_map, x, y) && !reachable[y * width + x]);
+
+        let Some(isolated) = isolated else {
+            return corridors;
+        };
+
+        let region = flood_fill(world_map, isolated);
+        let Some((from, to)) = closest_pair(world_map, &region, &reachable) else {
+            return corridors;
+        };
+
+        carve_corridor(world_map, from, to);
+        corridors += 1;
+    }
+}
+
+// BFS que ignora paredes, desde todas las celdas de la zona a la vez: la primera
+// celda alcanzable que toca es la más cercana (distancia Manhattan)
+fn closest_pair(
+    world_map: &WorldMap,
+    region: &[Option<usize>],
+    reachable: &[bool],
+) -> Option<((usize, usize), (usize, usize))> {
+    let width = world_map.width();
+    let mut origin = vec![None; region.len()];
+    let mut queue = VecDeque::new();
+
+    for (i, distance) in region.iter().enumerate() {
+        if distance.is_some() {
+            origin[i] = Some(i);
+            queue.push_back(i);
+        }
+    }
+
+    while let Some(i) = queue.pop_front() {
+        let (x, y) = (i % width, i / width);
+        if reachable[i] {
+            let from = origin[i]?;
+            return Some(((from % width, from / width), (x, y)));
+        }
+
+        for (nx, ny) in neighbors(x, y) {
+            if world_map.in_bounds(nx, ny) && origin[ny * width + nx].is_none() {
+                origin[ny * width + nx] = origin[i];
+                queue.push_back(ny * width + nx);
+            }
+        }
+    }
+
+    None
+}
+
+// Abre un pasillo en L (primero horizontal, luego vertical) sin tocar el borde del mapa
+fn carve_corridor(world_map: &mut WorldMap, from: (usize, usize), to: (usize, usize)) {
+    let max_x = world_map.width() - 2;
+    let max_y = world_map.height() - 2;
+    let open = |world_map: &mut WorldMap, x: usize, y: usize| {
+        let (x, y) = (x.clamp(1, max_x), y.clamp(1, max_y));
+        if world_map.get(x, y).is_some_and(|tile| tile.is_solid_for_player()) {
+            world_map.set(x, y, Tile::Empty);
+        }
+    };
+
+    for x in from.0.min(to.0)..=from.0.max(to.0) {
+        open(world_map, x, from.1);
+    }
+    for y in from.1.min(to.1)..=from.1.max(to.1) {
+        open(world_map, to.0, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::{GameState, SPAWN_CELL};
+    use crate::map_generators::GeneratorKind;
+
+    #[test]
+    fn generated_maps_are_fully_connected() {
+        for seed in 0..3000 {
+            let game_state = GameState::new(Some(seed));
+            let report = validate(&game_state.world_map, SPAWN_CELL, game_state.exit_position);
+
+            assert!(report.is_valid(), "semilla {} produjo un mapa inválido", seed);
+            assert_eq!(report.exit_path_length, Some(game_state.exit_path_length));
+        }
+    }
+
+    #[test]
+    fn generated_maps_of_other_sizes_are_fully_connected() {
+        let mut game_state = GameState::new(Some(0));
+        for seed in 0..500 {
+            for (width, height) in [(20, 20), (64, 48), (200, 200)] {
+                if (width, height) == (200, 200) && seed % 50 != 0 {
+                    continue;
+                }
+                game_state.seed = seed;
+                game_state.generate_world(width, height);
+                let report = validate(&game_state.world_map, SPAWN_CELL, game_state.exit_position);
+
+                assert!(report.is_valid(), "semilla {} en {}x{} produjo un mapa inválido", seed, width, height);
+            }
+        }
+    }
+
+    #[test]
+    fn every_generator_makes_connected_maps() {
+        let mut game_state = GameState::new(Some(0));
+        for generator in GeneratorKind::ALL {
+            game_state.forced_generator = Some(generator);
+            for seed in 0..300 {
+                for (width, height) in [(16, 16), (40, 30), (64, 48)] {
+                    game_state.seed = seed;
+                    game_state.generate_world(width, height);
+                    let report = validate(&game_state.world_map, SPAWN_CELL, game_state.exit_position);
+
+                    assert!(
+                        report.is_valid(),
+                        "{} con semilla {} en {}x{} produjo un mapa inválido",
+                        generator.name(), seed, width, height
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_campaign_level_is_fully_connected() {
+        for seed in 0..200 {
+            let mut game_state = GameState::new(Some(seed));
+            loop {
+                let report = validate(&game_state.world_map, SPAWN_CELL, game_state.exit_position);
+                assert!(report.is_valid(), "semilla {} en el nivel {} produjo un mapa inválido", seed, game_state.level_index);
+                if !game_state.reach_exit() {
+                    break;
+                }
+            }
+            assert!(game_state.escaped);
+        }
+    }
+
+    #[test]
+    fn isolated_areas_get_connected() {
+        let mut world_map = WorldMap::new(12, 12, Tile::Wall);
+        for (x, y) in [(1, 1), (2, 1), (3, 1), (8, 8), (9, 8), (9, 9)] {
+            world_map.set(x, y, Tile::Empty);
+        }
+        world_map.set(10, 9, Tile::Exit);
+
+        assert!(!validate(&world_map, (1, 1), (10, 9)).is_valid());
+
+        assert_eq!(connect_regions(&mut world_map, (1, 1)), 1);
+        let report = validate(&world_map, (1, 1), (10, 9));
+        assert!(report.is_valid());
+        assert_eq!(report.exit_path_length, Some(17));
+    }
+
+    #[test]
+    fn open_border_cells_do_not_stall_the_repair() {
+        let mut world_map = WorldMap::from_rows(&[
+            ".#####",
+            "#..#.#",
+            "#..#X#",
+            "######",
+        ]);
+
+        assert_eq!(connect_regions(&mut world_map, (1, 1)), 1);
+        assert_eq!(world_map.get(0, 0), Some(Tile::Wall));
+        assert!(validate(&world_map, (1, 1), (4, 2)).is_valid());
+    }
+}
@@ -1,11 +1,15 @@
 use macroquad::prelude::*;
 use crate::player::Player;
 use crate::enemy::Enemy;
+use crate::enemy_ai::AiState;
+use crate::world_map::TileMap;
 
 pub struct Minimap {
     size: f32,
     scale: f32,
     position: (f32, f32),
+    /// Modo de depuración: dibuja el camino que sigue el enemigo y su estado
+    pub debug: bool,
 }
 
 impl Minimap {
@@ -14,10 +18,11 @@ impl Minimap {
             size: 150.0, 
             scale: 5.0,  
             position: (10.0, 10.0),
+            debug: false,
         }
     }
     
-    pub fn draw(&self, player: &Player, world_map: &[[u8; 40]; 30]) { 
+    pub fn draw(&self, player: &Player, world_map: &dyn TileMap) { 
         let (map_x, map_y) = self.position;
         
         // Fondo del minimapa con mejor contraste
@@ -37,38 +42,24 @@ impl Minimap {
                 let world_x = player_map_x + dx as f32;
                 let world_y = player_map_y + dy as f32;
                 
-                // Verificar límites del mundo (40x30)
-                if world_x >= 0.0 && world_x < 40.0 && world_y >= 0.0 && world_y < 30.0 {
-                    let world_x_int = world_x as usize;
-                    let world_y_int = world_y as usize;
+                // Verificar límites del mundo
+                if let Some(cell) = world_map.tile_at(world_x, world_y) {
+                    // Calcular posición en pantalla centrada
+                    let screen_x = map_x + (dx + half_cells) as f32 * self.scale;
+                    let screen_y = map_y + (dy + half_cells) as f32 * self.scale;
                     
-                    // Asegurar que no salgamos de los límites del array
-                    if world_x_int < 40 && world_y_int < 30 {
-                        let cell = world_map[world_y_int][world_x_int];
+                    // Verificar que la celda esté dentro del minimapa
+                    if screen_x >= map_x && screen_x < map_x + self.size - self.scale &&
+                       screen_y >= map_y && screen_y < map_y + self.size - self.scale {
                         
-                        // Calcular posición en pantalla centrada
-                        let screen_x = map_x + (dx + half_cells) as f32 * self.scale;
-                        let screen_y = map_y + (dy + half_cells) as f32 * self.scale;
+                        let color = cell.props().minimap_color;
                         
-                        // Verificar que la celda esté dentro del minimapa
-                        if screen_x >= map_x && screen_x < map_x + self.size - self.scale &&
-                           screen_y >= map_y && screen_y < map_y + self.size - self.scale {
-                            
-                            let color = match cell {
-                                0 => Color::from_rgba(40, 40, 25, 255),   
-                                1 => Color::from_rgba(200, 190, 120, 255), 
-                                2 => Color::from_rgba(140, 60, 60, 255),   
-                                3 => Color::from_rgba(60, 220, 60, 255),   
-                                _ => GRAY,
-                            };
-                            
-                            draw_rectangle(screen_x, screen_y, self.scale, self.scale, color);
-                            
-                            // Añadir borde sutil a las paredes para mejor definición
-                            if cell == 1 || cell == 2 {
-                                draw_rectangle_lines(screen_x, screen_y, self.scale, self.scale, 1.0, 
-                                    Color::from_rgba(80, 80, 60, 100));
-                            }
+                        draw_rectangle(screen_x, screen_y, self.scale, self.scale, color);
+                        
+                        // Añadir borde sutil a las paredes para mejor definición
+                        if cell.is_solid_for_player() {
+                            draw_rectangle_lines(screen_x, screen_y, self.scale, self.scale, 1.0, 
+                                Color::from_rgba(80, 80, 60, 100));
                         }
                     }
                 }
@@ -100,12 +91,12 @@ impl Minimap {
             Color::from_rgba(180, 180, 180, 200));
     }
 
-    pub fn draw_with_enemy(&self, player: &Player, enemy: &Enemy, world_map: &[[u8; 40]; 30]) {
+    pub fn draw_with_enemies(&self, player: &Player, enemies: &[Enemy], world_map: &dyn TileMap) {
         // Primero dibujar el minimapa normal
         self.draw(player, world_map);
         
-        // Si el enemigo está activo, dibujarlo en el minimapa
-        if enemy.active {
+        // Dibujar en el minimapa cada enemigo activo
+        for (index, enemy) in enemies.iter().enumerate().filter(|(_, enemy)| enemy.active) {
             let (map_x, map_y) = self.position;
             let center_x = map_x + self.size / 2.0;
             let center_y = map_y + self.size / 2.0;
@@ -118,16 +109,29 @@ impl Minimap {
             let enemy_screen_x = center_x + relative_x * self.scale;
             let enemy_screen_y = center_y + relative_y * self.scale;
             
+            if self.debug {
+                self.draw_path(player, enemy);
+                let label = format!("{}: {}", enemy.archetype.name, enemy.state().name());
+                draw_text(&label, map_x, map_y + self.size + 60.0 + index as f32 * 14.0, 12.0,
+                    Color::from_rgba(80, 200, 255, 220));
+            }
+            
             // Solo dibujar el enemigo si está dentro del área visible del minimapa
             if enemy_screen_x >= map_x && enemy_screen_x <= map_x + self.size &&
                enemy_screen_y >= map_y && enemy_screen_y <= map_y + self.size {
                 
-                // Dibujar enemigo como un punto púrpura pulsante
+                // Dibujar enemigo como un punto pulsante: púrpura si no sabe del jugador,
+                // naranja si investiga o busca y rojo si persigue
                 let pulse = (get_time() * 6.0).sin() as f32 * 0.3 + 0.7;
                 let enemy_size = 3.0 + pulse;
+                let (r, g, b) = match enemy.state() {
+                    AiState::Wander => (150, 50, 200),
+                    AiState::Investigate | AiState::Search => (230, 140, 40),
+                    AiState::Chase => (230, 30, 30),
+                };
                 
                 draw_circle(enemy_screen_x, enemy_screen_y, enemy_size, 
-                    Color::from_rgba(150, 50, 200, (255.0 * pulse) as u8));
+                    Color::from_rgba(r, g, b, (255.0 * pulse) as u8));
                 
                 // Añadir un borde más oscuro para mejor visibilidad
                 draw_circle_lines(enemy_screen_x, enemy_screen_y, enemy_size, 2.0,
@@ -135,4 +139,27 @@ impl Minimap {
             }
         }
     }
-}
\ No newline at end of file
+    
+    // Camino del enemigo como línea desde su posición, recortado al área del minimapa
+    fn draw_path(&self, player: &Player, enemy: &Enemy) {
+        let (map_x, map_y) = self.position;
+        let center_x = map_x + self.size / 2.0;
+        let center_y = map_y + self.size / 2.0;
+        let to_screen = |x: f32, y: f32| {
+            (center_x + (x - player.x) * self.scale, center_y + (y - player.y) * self.scale)
+        };
+        let inside = |(x, y): (f32, f32)| {
+            x >= map_x && x <= map_x + self.size && y >= map_y && y <= map_y + self.size
+        };
+        
+        let mut previous = to_screen(enemy.x, enemy.y);
+        for &(x, y) in enemy.path() {
+            let point = to_screen(x, y);
+            if inside(previous) && inside(point) {
+                draw_line(previous.0, previous.1, point.0, point.1, 1.5, Color::from_rgba(80, 200, 255, 220));
+                draw_circle(point.0, point.1, 1.5, Color::from_rgba(80, 200, 255, 255));
+            }
+            previous = point;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// Ruido: las acciones del jugador generan ruidos con una intensidad, medida en
+// celdas de alcance. El ruido se propaga por la cuadrícula perdiendo una unidad
+// por celda libre y bastante más al atravesar una pared, así que se oye lejos
+// por los pasillos y poco a través de los muros.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use crate::world_map::TileMap;
+
+// Intensidad que se pierde al entrar en una celda libre y en una celda sólida
+const OPEN_DAMPING: u32 = 1;
+const WALL_DAMPING: u32 = 4;
+
+type Cell = (i32, i32);
+
+/// Ruido en un punto del mundo
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Noise {
+    pub x: f32,
+    pub y: f32,
+    /// Intensidad inicial: en un pasillo abierto llega a esta cantidad de celdas
+    pub loudness: f32,
+}
+
+/// Intensidad con la que un ruido llega a cada celda que alcanza
+pub struct NoiseField {
+    levels: HashMap<Cell, f32>,
+}
+
+impl NoiseField {
+    /// Propaga el ruido por el mapa. Las celdas fuera del mapa (o de los chunks cargados) no lo transmiten.
+    pub fn propagate(map: &dyn TileMap, noise: &Noise) -> Self {
+        let source = (noise.x.floor() as i32, noise.y.floor() as i32);
+        let mut cost: HashMap<Cell, u32> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        let mut levels = HashMap::new();
+
+        cost.insert(source, 0);
+        open.push(Reverse((0, source)));
+
+        while let Some(Reverse((cell_cost, cell))) = open.pop() {
+            if cost[&cell] < cell_cost {
+                continue;
+            }
+            levels.insert(cell, noise.loudness - cell_cost as f32);
+
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let next = (cell.0 + dx, cell.1 + dy);
+                let Some(tile) = map.tile(next.0, next.1) else {
+                    continue;
+                };
+                let damping = if tile.is_solid_for_player() { WALL_DAMPING } else { OPEN_DAMPING };
+                let next_cost = cell_cost + damping;
+                if next_cost as f32 >= noise.loudness {
+                    continue;
+                }
+                if cost.get(&next).is_none_or(|&known| next_cost < known) {
+                    cost.insert(next, next_cost);
+                    open.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+
+        Self { levels }
+    }
+
+    /// Intensidad que llega al punto (x, y); 0 si el ruido no alcanza
+    pub fn level_at(&self, x: f32, y: f32) -> f32 {
+        self.levels.get(&(x.floor() as i32, y.floor() as i32)).copied().unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_map::WorldMap;
+
+    #[test]
+    fn walls_dampen_noise() {
+        let map = WorldMap::from_rows(&[
+            "#########",
+            "#...#...#",
+            "#...#...#",
+            "#########",
+        ]);
+        let noise = Noise { x: 3.5, y: 1.5, loudness: 6.0 };
+        let field = NoiseField::propagate(&map, &noise);
+
+        // Misma distancia, pero una celda al otro lado de la pared oye mucho menos
+        assert_eq!(field.level_at(1.5, 1.5), 4.0);
+        assert_eq!(field.level_at(5.5, 1.5), 1.0);
+        assert_eq!(field.level_at(7.5, 1.5), 0.0);
+    }
+
+    #[test]
+    fn quiet_noises_do_not_travel() {
+        let map = WorldMap::from_rows(&[
+            "##########",
+            "#........#",
+            "##########",
+        ]);
+        let loud = NoiseField::propagate(&map, &Noise { x: 1.5, y: 1.5, loudness: 8.0 });
+        let quiet = NoiseField::propagate(&map, &Noise { x: 1.5, y: 1.5, loudness: 2.0 });
+
+        assert!(loud.level_at(7.5, 1.5) > 0.0);
+        assert!(quiet.level_at(2.5, 1.5) > 0.0);
+        assert_eq!(quiet.level_at(4.5, 1.5), 0.0);
+    }
+}
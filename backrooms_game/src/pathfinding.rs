@@ -0,0 +1,226 @@
+// Búsqueda de caminos A* sobre la cuadrícula para los enemigos, con suavizado
+// del resultado. Funciona con cualquier TileMap, incluido el mundo infinito, así
+// que la búsqueda se limita por cantidad de nodos en vez de por tamaño de mapa.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use crate::world_map::TileMap;
+
+// Nodos expandidos como máximo en una búsqueda antes de rendirse
+const MAX_EXPANDED_NODES: usize = 6000;
+// Paso con el que se recorre un segmento al comprobar si está libre
+const CLEARANCE_STEP: f32 = 0.1;
+// Puntos hacia adelante que el suavizado intenta saltar de una vez
+const SMOOTHING_LOOKAHEAD: usize = 12;
+
+type Cell = (i32, i32);
+
+fn is_walkable(map: &dyn TileMap, (x, y): Cell) -> bool {
+    map.tile(x, y).is_some_and(|tile| !tile.is_solid_for_enemy())
+}
+
+// Distancia octil: costo exacto en una cuadrícula sin paredes con diagonales
+fn heuristic(a: Cell, b: Cell) -> f32 {
+    let dx = (a.0 - b.0).abs() as f32;
+    let dy = (a.1 - b.1).abs() as f32;
+    dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+}
+
+// Entrada de la cola de prioridad, ordenada para que BinaryHeap saque el menor costo
+struct Node {
+    estimate: f32,
+    cell: Cell,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// Camino de celdas de `start` a `goal` (ambas incluidas) en 8 direcciones. Las
+/// diagonales solo se permiten si no cortan la esquina de una pared.
+pub fn find_path(map: &dyn TileMap, start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+    if !is_walkable(map, start) || !is_walkable(map, goal) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut cost: HashMap<Cell, f32> = HashMap::new();
+    let mut came_from: HashMap<Cell, Cell> = HashMap::new();
+
+    cost.insert(start, 0.0);
+    open.push(Node { estimate: heuristic(start, goal), cell: start });
+    let mut expanded = 0;
+
+    while let Some(Node { estimate, cell }) = open.pop() {
+        if cell == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        // Entrada vieja de una celda que ya se alcanzó por un camino más corto
+        let cell_cost = cost[&cell];
+        if estimate > cell_cost + heuristic(cell, goal) + 0.001 {
+            continue;
+        }
+
+        expanded += 1;
+        if expanded > MAX_EXPANDED_NODES {
+            return None;
+        }
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let next = (cell.0 + dx, cell.1 + dy);
+                if !is_walkable(map, next) {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                if diagonal && (!is_walkable(map, (cell.0 + dx, cell.1)) || !is_walkable(map, (cell.0, cell.1 + dy))) {
+                    continue;
+                }
+
+                let step = if diagonal { std::f32::consts::SQRT_2 } else { 1.0 };
+                let next_cost = cell_cost + step;
+                if cost.get(&next).is_none_or(|&known| next_cost < known) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, cell);
+                    open.push(Node { estimate: next_cost + heuristic(next, goal), cell: next });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Verdadero si un cuerpo de radio `radius` puede ir en línea recta de `from` a `to`
+/// sin tocar celdas sólidas para el enemigo
+pub fn is_clear(map: &dyn TileMap, from: (f32, f32), to: (f32, f32), radius: f32) -> bool {
+    let dx = to.0 - from.0;
+    let dy = to.1 - from.1;
+    let steps = ((dx * dx + dy * dy).sqrt() / CLEARANCE_STEP).ceil().max(1.0) as usize;
+
+    (0..=steps).all(|step| {
+        let t = step as f32 / steps as f32;
+        let x = from.0 + dx * t;
+        let y = from.1 + dy * t;
+        [(-radius, -radius), (radius, -radius), (-radius, radius), (radius, radius)]
+            .iter()
+            .all(|&(ox, oy)| map.tile_at(x + ox, y + oy).is_some_and(|tile| !tile.is_solid_for_enemy()))
+    })
+}
+
+/// Convierte un camino de celdas en puntos del mundo, saltando todos los intermedios
+/// que se pueden recorrer en línea recta con el radio dado (string pulling)
+pub fn smooth_path(map: &dyn TileMap, start: (f32, f32), path: &[Cell], radius: f32) -> Vec<(f32, f32)> {
+    let points: Vec<(f32, f32)> = path.iter().map(|&(x, y)| (x as f32 + 0.5, y as f32 + 0.5)).collect();
+    let mut smoothed = Vec::new();
+    let mut anchor = start;
+    let mut index = 0;
+
+    while index < points.len() {
+        // El punto más lejano visible desde el ancla; el siguiente siempre sirve como respaldo
+        let mut farthest = index;
+        let last = (index + SMOOTHING_LOOKAHEAD).min(points.len() - 1);
+        for candidate in (index + 1..=last).rev() {
+            if is_clear(map, anchor, points[candidate], radius) {
+                farthest = candidate;
+                break;
+            }
+        }
+
+        smoothed.push(points[farthest]);
+        anchor = points[farthest];
+        index = farthest + 1;
+    }
+
+    smoothed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_map::WorldMap;
+
+    #[test]
+    fn finds_shortest_path_around_walls() {
+        let map = WorldMap::from_rows(&[
+            "#######",
+            "#.....#",
+            "#.###.#",
+            "#.#.#.#",
+            "#.#.#.#",
+            "#######",
+        ]);
+
+        let path = find_path(&map, (1, 4), (5, 4)).unwrap();
+        assert_eq!(path.first(), Some(&(1, 4)));
+        assert_eq!(path.last(), Some(&(5, 4)));
+        // Sube, cruza por arriba y baja: 3 + 4 + 3 pasos
+        assert_eq!(path.len(), 11);
+
+        assert_eq!(find_path(&map, (1, 4), (3, 4)), None);
+    }
+
+    #[test]
+    fn diagonals_do_not_cut_corners() {
+        let map = WorldMap::from_rows(&[
+            "####",
+            "#.##",
+            "#..#",
+            "####",
+        ]);
+
+        assert_eq!(find_path(&map, (1, 1), (2, 2)), Some(vec![(1, 1), (1, 2), (2, 2)]));
+    }
+
+    #[test]
+    fn smoothing_skips_cells_in_open_space() {
+        let map = WorldMap::from_rows(&[
+            "########",
+            "#......#",
+            "#......#",
+            "#......#",
+            "#.####.#",
+            "########",
+        ]);
+
+        let start = (1.5, 1.5);
+        let path = find_path(&map, (1, 1), (6, 4)).unwrap();
+        let smoothed = smooth_path(&map, start, &path, 0.3);
+
+        assert!(smoothed.len() < path.len());
+        assert_eq!(smoothed.last(), Some(&(6.5, 4.5)));
+
+        let mut from = start;
+        for &point in &smoothed {
+            assert!(is_clear(&map, from, point, 0.3));
+            from = point;
+        }
+    }
+}
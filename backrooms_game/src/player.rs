@@ -1,4 +1,82 @@
 use macroquad::prelude::*;
+use crate::flashlight::Flashlight;
+use crate::input::{Action, Input};
+use crate::noise::Noise;
+use crate::settings::Settings;
+use crate::world_map::TileMap;
+
+// Ruido de los pasos (uno por zancada) caminando, corriendo y agachado a toda velocidad,
+// de chocar contra una pared, de prender o apagar la linterna y de recoger una batería
+const STRIDE_LENGTH: f32 = 0.8;
+const WALK_LOUDNESS: f32 = 7.0;
+const SPRINT_LOUDNESS: f32 = 11.0;
+const CROUCH_LOUDNESS: f32 = 2.0;
+const BUMP_LOUDNESS: f32 = 5.0;
+const BUMP_COOLDOWN: f32 = 0.6;
+const FLASHLIGHT_LOUDNESS: f32 = 1.5;
+const PICKUP_LOUDNESS: f32 = 3.0;
+// Agachado se mueve más lento, corriendo más rápido, y agotado más lento que caminando
+const CROUCH_SPEED_FACTOR: f32 = 0.5;
+const SPRINT_SPEED_FACTOR: f32 = 1.9;
+const EXHAUSTED_SPEED_FACTOR: f32 = 0.6;
+// Stamina: segundos sin correr antes de empezar a recuperarla, segundos para llenarla
+// desde cero, y fracción que hay que recuperar para dejar de estar agotado
+const STAMINA_REGEN_DELAY: f32 = 1.5;
+const STAMINA_REGEN_TIME: f32 = 6.0;
+const EXHAUSTION_RECOVERY: f32 = 0.35;
+// Stamina hasta que se aplique la de la dificultad
+const DEFAULT_STAMINA: f32 = 5.0;
+// Radianes que gira la vista por pixel de mouse con sensibilidad 1
+const LOOK_SENSITIVITY: f32 = 0.001;
+// Mirar arriba/abajo: fracción de la pantalla que se mueve el horizonte por pixel de
+// mouse, y cuánto puede alejarse del centro
+const PITCH_SENSITIVITY: f32 = 0.0015;
+const MAX_PITCH: f32 = 0.35;
+// Balanceo: altura que sube y baja la cámara en cada paso caminando (1.0 = una pared),
+// tope al correr, y qué tan rápido se asienta al cambiar de paso o detenerse
+const BOB_HEIGHT: f32 = 0.025;
+const MAX_BOB: f32 = 0.02;
+const BOB_SMOOTHING: f32 = 8.0;
+
+/// Reserva para correr, medida en segundos de carrera
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    /// Se quedó sin stamina: no puede correr y camina más lento hasta recuperarse
+    pub exhausted: bool,
+    rest_timer: f32,
+}
+
+impl Stamina {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max, exhausted: false, rest_timer: 0.0 }
+    }
+
+    /// Gasta stamina si está corriendo, o la recupera después de un rato sin correr
+    pub fn update(&mut self, dt: f32, sprinting: bool) {
+        if sprinting {
+            self.current = (self.current - dt).max(0.0);
+            self.rest_timer = 0.0;
+            if self.current == 0.0 {
+                self.exhausted = true;
+            }
+        } else {
+            self.rest_timer += dt;
+            if self.rest_timer >= STAMINA_REGEN_DELAY {
+                self.current = (self.current + self.max / STAMINA_REGEN_TIME * dt).min(self.max);
+            }
+        }
+
+        if self.exhausted && self.current >= self.max * EXHAUSTION_RECOVERY {
+            self.exhausted = false;
+        }
+    }
+
+    /// Fracción disponible, de 0 a 1
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 { self.current / self.max } else { 0.0 }
+    }
+}
 
 pub struct Player {
     pub x: f32,
@@ -6,9 +84,25 @@ pub struct Player {
     pub angle: f32,
     speed: f32,
     turn_speed: f32,
-    last_mouse_x: f32,
+    // Movimiento del mouse que el suavizado todavía no aplicó a la vista
+    look_pending: Vec2,
+    /// Desplazamiento del horizonte como fracción de la pantalla (positivo = mira hacia arriba)
+    pub pitch: f32,
+    /// Cuánto está la cámara por encima (o debajo) de la mitad de la pared, por el balanceo
+    pub bob: f32,
+    // Intensidad del balanceo según lo rápido que camina, suavizada
+    bob_amount: f32,
     was_moving: bool,
     pub moving: bool,
+    pub crouching: bool,
+    pub sprinting: bool,
+    pub stamina: Stamina,
+    pub flashlight: Flashlight,
+    // Distancia recorrida desde el último paso, tiempo hasta que un choque vuelva a sonar,
+    // y ruidos producidos que todavía no se repartieron
+    stride: f32,
+    bump_cooldown: f32,
+    noises: Vec<Noise>,
 }
 
 impl Player {
@@ -19,97 +113,216 @@ impl Player {
             angle,
             speed: 3.0, 
             turn_speed: 2.5,
-            last_mouse_x: 0.0,
+            look_pending: Vec2::ZERO,
+            pitch: 0.0,
+            bob: 0.0,
+            bob_amount: 0.0,
             was_moving: false,
             moving: false,
+            crouching: false,
+            sprinting: false,
+            stamina: Stamina::new(DEFAULT_STAMINA),
+            // Cada partida la reemplaza por una sembrada con su semilla
+            flashlight: Flashlight::new(0),
+            stride: 0.0,
+            bump_cooldown: 0.0,
+            noises: Vec::new(),
         }
     }
     
-    pub fn update(&mut self, dt: f32, world_map: &[[u8; 40]; 30]) { 
-        self.was_moving = self.moving;
+    /// Coloca al jugador en el inicio de una partida nueva
+    pub fn respawn(&mut self, (x, y, angle): (f32, f32, f32)) {
+        self.x = x;
+        self.y = y;
+        self.angle = angle;
+        self.was_moving = false;
         self.moving = false;
+        self.crouching = false;
+        self.sprinting = false;
+        self.stamina = Stamina::new(self.stamina.max);
+        self.stride = 0.0;
+        self.pitch = 0.0;
+        self.look_pending = Vec2::ZERO;
+        self.bob = 0.0;
+        self.bob_amount = 0.0;
+        self.noises.clear();
+    }
+    
+    /// Fila de pantalla donde queda el horizonte
+    pub fn horizon(&self, screen_height: f32) -> f32 {
+        screen_height * (0.5 + self.pitch)
+    }
+    
+    /// Altura de los ojos como fracción de la altura de una pared
+    pub fn eye_height(&self) -> f32 {
+        0.5 + self.bob
+    }
+    
+    /// Segundos de carrera con la stamina llena (según la dificultad); la deja llena
+    pub fn set_stamina(&mut self, seconds: f32) {
+        self.stamina = Stamina::new(seconds);
+    }
+    
+    /// Ruidos producidos desde la última llamada
+    pub fn take_noises(&mut self) -> Vec<Noise> {
+        std::mem::take(&mut self.noises)
+    }
+    
+    fn make_noise(&mut self, loudness: f32) {
+        self.noises.push(Noise { x: self.x, y: self.y, loudness });
+    }
+    
+    /// Prende o apaga la linterna; el clic se oye
+    pub fn toggle_flashlight(&mut self) {
+        self.flashlight.toggle();
+        self.make_noise(FLASHLIGHT_LOUDNESS);
+    }
+    
+    /// Recarga la linterna con una batería del suelo, haciendo ruido al levantarla
+    pub fn pick_up_battery(&mut self, charge: f32) {
+        self.flashlight.recharge(charge);
+        self.make_noise(PICKUP_LOUDNESS);
+    }
+    
+    // Ruido de un paso según cómo se mueve; `speed_fraction` es la parte de la velocidad
+    // máxima del modo actual a la que de verdad avanzó (caminar despacio se oye menos)
+    fn footstep_loudness(&self, speed_fraction: f32) -> f32 {
+        let loudness = if self.crouching {
+            CROUCH_LOUDNESS
+        } else if self.sprinting {
+            SPRINT_LOUDNESS
+        } else {
+            WALK_LOUDNESS
+        };
+        loudness * speed_fraction.clamp(0.0, 1.0)
+    }
+    
+    pub fn update(&mut self, dt: f32, world_map: &dyn TileMap, input: &Input, settings: &Settings) { 
+        self.was_moving = self.moving;
+        self.crouching = input.down(Action::Crouch);
+        self.bump_cooldown = (self.bump_cooldown - dt).max(0.0);
         
-        // Movimiento con WASD
-        let mut dx = 0.0;
-        let mut dy = 0.0;
-        
-        if is_key_down(KeyCode::W) {
-            dx += self.angle.cos();
-            dy += self.angle.sin();
-            self.moving = true;
-        }
-        if is_key_down(KeyCode::S) {
-            dx -= self.angle.cos();
-            dy -= self.angle.sin();
-            self.moving = true;
-        }
-        if is_key_down(KeyCode::A) {
-            dx += (self.angle - std::f32::consts::PI / 2.0).cos();
-            dy += (self.angle - std::f32::consts::PI / 2.0).sin();
-            self.moving = true;
-        }
-        if is_key_down(KeyCode::D) {
-            dx += (self.angle + std::f32::consts::PI / 2.0).cos();
-            dy += (self.angle + std::f32::consts::PI / 2.0).sin();
-            self.moving = true;
+        if input.pressed(Action::Flashlight) {
+            self.toggle_flashlight();
         }
         
-        // Normalizar movimiento diagonal
-        if dx != 0.0 && dy != 0.0 {
-            let length = (dx * dx + dy * dy).sqrt();
-            dx /= length;
-            dy /= length;
-        }
+        // Movimiento relativo a la vista; con el stick a medias se mueve más lento
+        let movement = input.movement();
+        self.moving = movement != Vec2::ZERO;
+        let forward = vec2(self.angle.cos(), self.angle.sin());
+        let right = vec2(-forward.y, forward.x);
+        let direction = forward * movement.y + right * movement.x;
+        let (mut dx, mut dy) = (direction.x, direction.y);
+        
+        // Correr gasta stamina; sin stamina queda agotado y se mueve más lento
+        self.sprinting = input.down(Action::Sprint) && self.moving && !self.crouching && !self.stamina.exhausted;
+        self.stamina.update(dt, self.sprinting);
         
         // Aplicar velocidad y delta time
-        dx *= self.speed * dt;
-        dy *= self.speed * dt;
+        let speed = self.speed * if self.crouching {
+            CROUCH_SPEED_FACTOR
+        } else if self.sprinting {
+            SPRINT_SPEED_FACTOR
+        } else if self.stamina.exhausted {
+            EXHAUSTED_SPEED_FACTOR
+        } else {
+            1.0
+        };
+        dx *= speed * dt;
+        dy *= speed * dt;
         
         // Verificar colisiones y mover
-        self.move_with_collision(dx, dy, world_map);
+        let (old_x, old_y) = (self.x, self.y);
+        let blocked = self.move_with_collision(dx, dy, world_map);
         
-        // Rotación con mouse
-        let (mouse_x, _) = mouse_position();
-        let mouse_delta = mouse_x - self.last_mouse_x;
+        // Un paso cada zancada; agachado o despacio casi no se oye
+        let moved = ((self.x - old_x).powi(2) + (self.y - old_y).powi(2)).sqrt();
+        self.stride += moved;
+        if self.stride >= STRIDE_LENGTH {
+            self.stride -= STRIDE_LENGTH;
+            let full_step = speed * dt;
+            let speed_fraction = if full_step > 0.0 { moved / full_step } else { 0.0 };
+            self.make_noise(self.footstep_loudness(speed_fraction));
+        }
         
-        if mouse_delta.abs() > 0.5 {
-            self.angle += mouse_delta * 0.001; 
-            
-            if self.angle < 0.0 {
-                self.angle += std::f32::consts::PI * 2.0;
-            } else if self.angle >= std::f32::consts::PI * 2.0 {
-                self.angle -= std::f32::consts::PI * 2.0;
-            }
+        // Chocar de frente contra una pared (no rozarla de costado) hace ruido
+        let intended = (dx * dx + dy * dy).sqrt();
+        if blocked && moved < intended * 0.5 && !self.crouching && self.bump_cooldown <= 0.0 {
+            self.bump_cooldown = BUMP_COOLDOWN;
+            self.make_noise(BUMP_LOUDNESS);
         }
         
-        self.last_mouse_x = mouse_x;
+        self.update_head_bob(dt, moved, settings.head_bob);
         
-        // Rotación con flechas como alternativa
-        if is_key_down(KeyCode::Left) {
-            self.angle -= self.turn_speed * dt;
-        }
-        if is_key_down(KeyCode::Right) {
-            self.angle += self.turn_speed * dt;
+        // Mirar con el mouse o el stick derecho, y girar con teclas como alternativa
+        self.look(input.look_delta(dt), dt, settings);
+        self.angle += input.turn() * self.turn_speed * dt;
+        self.angle = self.angle.rem_euclid(std::f32::consts::TAU);
+    }
+    
+    // Gira la vista según `delta` pixeles de mouse (o su equivalente con el stick). Con
+    // suavizado, cada cuadro se aplica una parte de lo que falta que depende solo de dt,
+    // así que el giro total y lo que tarda en llegar son los mismos a cualquier FPS.
+    fn look(&mut self, delta: Vec2, dt: f32, settings: &Settings) {
+        self.look_pending += delta;
+        let applied = if settings.mouse_smoothing > 0.0 {
+            self.look_pending * (1.0 - (-dt / settings.mouse_smoothing).exp())
+        } else {
+            self.look_pending
+        };
+        self.look_pending -= applied;
+
+        let turn = if settings.invert_x { -applied.x } else { applied.x };
+        let tilt = if settings.invert_y { -applied.y } else { applied.y };
+        self.angle += turn * LOOK_SENSITIVITY * settings.mouse_sensitivity;
+
+        // Mirar arriba y abajo mueve el horizonte, con un tope para no dar vuelta la vista
+        self.pitch = if settings.look_pitch {
+            (self.pitch - tilt * PITCH_SENSITIVITY * settings.mouse_sensitivity).clamp(-MAX_PITCH, MAX_PITCH)
+        } else {
+            0.0
+        };
+    }
+    
+    // La cámara baja con cada paso (al completar una zancada) y sube entre medio, más
+    // cuanto más rápido camina; al detenerse vuelve de a poco a la altura normal
+    fn update_head_bob(&mut self, dt: f32, moved: f32, enabled: bool) {
+        if !enabled {
+            self.bob = 0.0;
+            self.bob_amount = 0.0;
+            return;
         }
+        let target = if dt > 0.0 { moved / dt / self.speed } else { 0.0 };
+        self.bob_amount += (target - self.bob_amount) * (1.0 - (-BOB_SMOOTHING * dt).exp());
+        let step = (self.stride / STRIDE_LENGTH * std::f32::consts::PI).sin().abs();
+        self.bob = (BOB_HEIGHT * self.bob_amount * (step - 0.5)).clamp(-MAX_BOB, MAX_BOB);
     }
     
-    fn move_with_collision(&mut self, dx: f32, dy: f32, world_map: &[[u8; 40]; 30]) {
+    // Devuelve verdadero si una pared frenó el movimiento en algún eje
+    fn move_with_collision(&mut self, dx: f32, dy: f32, world_map: &dyn TileMap) -> bool {
         let collision_padding = 0.2;
+        let mut blocked = false;
         
         // Probar movimiento en X
         let new_x = self.x + dx;
         if self.can_move_to(new_x, self.y, collision_padding, world_map) {
             self.x = new_x;
+        } else {
+            blocked |= dx != 0.0;
         }
         
         // Probar movimiento en Y
         let new_y = self.y + dy;
         if self.can_move_to(self.x, new_y, collision_padding, world_map) {
             self.y = new_y;
+        } else {
+            blocked |= dy != 0.0;
         }
+        
+        blocked
     }
     
-    fn can_move_to(&self, x: f32, y: f32, padding: f32, world_map: &[[u8; 40]; 30]) -> bool {
+    fn can_move_to(&self, x: f32, y: f32, padding: f32, world_map: &dyn TileMap) -> bool {
         let corners = [
             (x - padding, y - padding),
             (x + padding, y - padding),
@@ -117,22 +330,104 @@ impl Player {
             (x + padding, y + padding),
         ];
         
-        for (corner_x, corner_y) in corners.iter() {
-            let map_x = *corner_x as usize;
-            let map_y = *corner_y as usize;
-            
-            // Verificar límites del mapa (40x30)
-            if map_y >= world_map.len() || map_x >= world_map[0].len() {
-                return false;
-            }
-            
-            // Verificar colisión con paredes (pero permitir salida)
-            let cell = world_map[map_y][map_x];
-            if cell == 1 || cell == 2 {
-                return false;
+        for &(corner_x, corner_y) in corners.iter() {
+            // Verificar límites del mapa y colisión con celdas sólidas
+            match world_map.tile_at(corner_x, corner_y) {
+                Some(tile) if !tile.is_solid_for_player() => {}
+                _ => return false,
             }
         }
         
         true
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_out_of_stamina_exhausts_until_it_recovers() {
+        let mut stamina = Stamina::new(2.0);
+        for _ in 0..25 {
+            stamina.update(0.1, true);
+        }
+        assert_eq!(stamina.current, 0.0);
+        assert!(stamina.exhausted);
+
+        // Nada se recupera durante la espera inicial
+        stamina.update(STAMINA_REGEN_DELAY - 0.1, false);
+        assert_eq!(stamina.current, 0.0);
+
+        let mut seconds = 0.0;
+        while stamina.exhausted {
+            stamina.update(0.1, false);
+            seconds += 0.1;
+        }
+        assert!((seconds - STAMINA_REGEN_TIME * EXHAUSTION_RECOVERY).abs() < 0.5, "{}", seconds);
+        assert!(stamina.fraction() < 1.0);
+    }
+
+    #[test]
+    fn head_bob_follows_steps_and_settles_when_stopped() {
+        let mut player = Player::new(1.5, 1.5, 0.0);
+        let dt = 1.0 / 60.0;
+
+        // Corriendo: la cámara sube y baja pero nunca pasa del tope
+        let mut heights = Vec::new();
+        for _ in 0..120 {
+            let moved = player.speed * SPRINT_SPEED_FACTOR * dt;
+            player.stride = (player.stride + moved) % STRIDE_LENGTH;
+            player.update_head_bob(dt, moved, true);
+            heights.push(player.bob);
+        }
+        assert!(heights.iter().all(|bob| bob.abs() <= MAX_BOB));
+        assert!(heights.iter().any(|&bob| bob > 0.0) && heights.iter().any(|&bob| bob < 0.0));
+
+        for _ in 0..120 {
+            player.update_head_bob(dt, 0.0, true);
+        }
+        assert!(player.bob.abs() < 1e-3);
+
+        player.update_head_bob(dt, 0.1, false);
+        assert_eq!(player.bob, 0.0);
+    }
+
+    #[test]
+    fn smoothed_mouse_look_does_not_depend_on_frame_rate() {
+        let settings = Settings { mouse_smoothing: 0.1, invert_y: true, ..Settings::default() };
+
+        // El mismo movimiento de mouse repartido en un segundo a 30 y a 240 FPS
+        let turn_at = |fps: usize| {
+            let mut player = Player::new(1.5, 1.5, 1.0);
+            let dt = 1.0 / fps as f32;
+            for frame in 0..fps * 2 {
+                let delta = if frame < fps { vec2(300.0, 40.0) / fps as f32 } else { Vec2::ZERO };
+                player.look(delta, dt, &settings);
+            }
+            (player.angle, player.pitch)
+        };
+        let (slow, fast) = (turn_at(30), turn_at(240));
+        assert!((slow.0 - fast.0).abs() < 1e-3 && (slow.1 - fast.1).abs() < 1e-3, "{:?} {:?}", slow, fast);
+        assert!((fast.0 - (1.0 + 300.0 * LOOK_SENSITIVITY)).abs() < 1e-3);
+        // Invertido: mover el mouse hacia abajo mira hacia arriba
+        assert!(fast.1 > 0.0);
+    }
+
+    #[test]
+    fn interacting_and_slow_steps_make_noise_accordingly() {
+        let mut player = Player::new(1.5, 1.5, 0.0);
+
+        player.toggle_flashlight();
+        player.pick_up_battery(0.5);
+        let loudness: Vec<f32> = player.take_noises().iter().map(|noise| noise.loudness).collect();
+        assert_eq!(loudness, vec![FLASHLIGHT_LOUDNESS, PICKUP_LOUDNESS]);
+        assert!(!player.flashlight.on);
+
+        // Con el stick a medias los pasos se oyen menos que a toda velocidad
+        assert_eq!(player.footstep_loudness(1.0), WALK_LOUDNESS);
+        assert!(player.footstep_loudness(0.3) < WALK_LOUDNESS * 0.5);
+        player.crouching = true;
+        assert!(player.footstep_loudness(1.0) < WALK_LOUDNESS);
+    }
+}
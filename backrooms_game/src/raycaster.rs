@@ -0,0 +1,160 @@
+// Raycaster DDA sobre la cuadrícula del mapa. Lo comparten el renderizado de paredes
+// y las pruebas de visibilidad del enemigo, así ambos ven exactamente lo mismo.
+
+use crate::tile::Tile;
+use crate::world_map::TileMap;
+
+/// Cara de la celda de pared que recibió el rayo (y crece hacia el sur)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WallSide {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl WallSide {
+    /// Caras perpendiculares al eje X (el rayo cruzó un borde vertical de la cuadrícula)
+    pub fn is_vertical(self) -> bool {
+        matches!(self, WallSide::East | WallSide::West)
+    }
+}
+
+pub struct RayHit {
+    pub distance: f32,
+    pub tile: Tile,
+    pub side: WallSide,
+    /// Coordenada horizontal de textura (0.0..1.0) a lo largo de la cara golpeada
+    pub tex_u: f32,
+}
+
+/// Lanza un rayo desde (origin_x, origin_y) con el ángulo dado hasta la primera pared.
+/// Salir del mapa o de la zona cargada cuenta como golpear una pared normal.
+pub fn cast_ray(world_map: &dyn TileMap, origin_x: f32, origin_y: f32, angle: f32) -> RayHit {
+    traverse(world_map, origin_x, origin_y, angle.cos(), angle.sin(), f32::INFINITY)
+        .expect("un rayo sin distancia máxima siempre termina en una pared")
+}
+
+/// Verdadero si ninguna pared se interpone en el segmento entre ambos puntos
+pub fn has_line_of_sight(world_map: &dyn TileMap, from_x: f32, from_y: f32, to_x: f32, to_y: f32) -> bool {
+    let dx = to_x - from_x;
+    let dy = to_y - from_y;
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    if distance == 0.0 {
+        return true;
+    }
+
+    traverse(world_map, from_x, from_y, dx / distance, dy / distance, distance).is_none()
+}
+
+// Recorre las celdas que atraviesa el rayo (dirección unitaria) una por una.
+// Devuelve None si no encuentra pared antes de max_distance.
+fn traverse(
+    world_map: &dyn TileMap,
+    origin_x: f32,
+    origin_y: f32,
+    dir_x: f32,
+    dir_y: f32,
+    max_distance: f32,
+) -> Option<RayHit> {
+    let mut map_x = origin_x.floor() as i32;
+    let mut map_y = origin_y.floor() as i32;
+
+    // Distancia que recorre el rayo para cruzar una celda completa en cada eje
+    let delta_dist_x = if dir_x == 0.0 { f32::INFINITY } else { (1.0 / dir_x).abs() };
+    let delta_dist_y = if dir_y == 0.0 { f32::INFINITY } else { (1.0 / dir_y).abs() };
+
+    let (step_x, mut side_dist_x) = if dir_x < 0.0 {
+        (-1, (origin_x - map_x as f32) * delta_dist_x)
+    } else {
+        (1, (map_x as f32 + 1.0 - origin_x) * delta_dist_x)
+    };
+    let (step_y, mut side_dist_y) = if dir_y < 0.0 {
+        (-1, (origin_y - map_y as f32) * delta_dist_y)
+    } else {
+        (1, (map_y as f32 + 1.0 - origin_y) * delta_dist_y)
+    };
+
+    loop {
+        let (distance, side) = if side_dist_x < side_dist_y {
+            let distance = side_dist_x;
+            side_dist_x += delta_dist_x;
+            map_x += step_x;
+            (distance, if step_x > 0 { WallSide::West } else { WallSide::East })
+        } else {
+            let distance = side_dist_y;
+            side_dist_y += delta_dist_y;
+            map_y += step_y;
+            (distance, if step_y > 0 { WallSide::North } else { WallSide::South })
+        };
+
+        if distance > max_distance {
+            return None;
+        }
+
+        let tile = world_map.tile(map_x, map_y).unwrap_or(Tile::Wall);
+
+        if tile.blocks_sight() {
+            let hit_x = origin_x + dir_x * distance;
+            let hit_y = origin_y + dir_y * distance;
+
+            // Invertir según la cara para que las texturas no se vean espejadas
+            let along = if side.is_vertical() { hit_y - hit_y.floor() } else { hit_x - hit_x.floor() };
+            let tex_u = match side {
+                WallSide::East | WallSide::North => 1.0 - along,
+                WallSide::West | WallSide::South => along,
+            };
+
+            return Some(RayHit { distance, tile, side, tex_u });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_map::WorldMap;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    const ROOM: [&str; 5] = ["#####", "#...#", "#...#", "#...#", "#####"];
+
+    fn assert_hit(hit: &RayHit, distance: f32, side: WallSide) {
+        assert!((hit.distance - distance).abs() < 1e-4, "distancia {} en vez de {}", hit.distance, distance);
+        assert_eq!(hit.side, side);
+        assert_eq!(hit.tile, Tile::Wall);
+    }
+
+    #[test]
+    fn hits_the_nearest_wall_face() {
+        let map = WorldMap::from_rows(&ROOM);
+        assert_hit(&cast_ray(&map, 1.5, 2.5, 0.0), 2.5, WallSide::West);
+        assert_hit(&cast_ray(&map, 1.5, 2.5, PI), 0.5, WallSide::East);
+        assert_hit(&cast_ray(&map, 1.5, 2.5, FRAC_PI_2), 1.5, WallSide::North);
+        assert_hit(&cast_ray(&map, 1.5, 2.5, -FRAC_PI_2), 1.5, WallSide::South);
+
+        // A 45° el rayo entra a la pared por la esquina (4, 4) tras cruzar dos celdas
+        let diagonal = cast_ray(&map, 1.5, 1.5, PI / 4.0);
+        assert!((diagonal.distance - 2.5 * 2.0f32.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn leaving_the_map_counts_as_a_wall() {
+        let map = WorldMap::from_rows(&["...", "...", "..."]);
+        let hit = cast_ray(&map, 0.5, 1.5, 0.0);
+        assert_hit(&hit, 2.5, WallSide::West);
+    }
+
+    #[test]
+    fn line_of_sight_is_blocked_by_walls_only() {
+        let map = WorldMap::from_rows(&["#######", "#.....#", "#..#..#", "#.....#", "#######"]);
+        assert!(!has_line_of_sight(&map, 1.5, 2.5, 5.5, 2.5));
+        assert!(has_line_of_sight(&map, 1.5, 1.5, 5.5, 1.5));
+        // En diagonal la columna también tapa, en ambos sentidos
+        assert!(!has_line_of_sight(&map, 1.5, 3.5, 5.5, 1.5));
+        assert!(!has_line_of_sight(&map, 5.5, 1.5, 1.5, 3.5));
+        // El mismo punto y los vecinos inmediatos siempre se ven
+        assert!(has_line_of_sight(&map, 2.5, 2.5, 2.5, 2.5));
+        assert!(has_line_of_sight(&map, 2.5, 2.5, 2.9, 2.5));
+    }
+}
@@ -0,0 +1,238 @@
+use macroquad::prelude::*;
+use crate::campaign::LevelDef;
+use crate::flashlight::beam;
+use crate::lighting::LightMap;
+use crate::player::Player;
+use crate::raycaster::{cast_ray, RayHit};
+use crate::sprites::{draw_sprites, is_sprite_visible, Sprite};
+use crate::textures::TextureManager;
+use crate::world_map::TileMap;
+
+pub const FOV: f32 = std::f32::consts::PI / 3.0;
+// Altura proyectada de una pared a distancia 1, como fracción de la altura de pantalla
+pub const WALL_SCALE: f32 = 0.6;
+// Luz mínima donde no llega ninguna lámpara ni la linterna: casi oscuridad
+const MIN_AMBIENT: f32 = 0.04;
+// Las paredes toman la luz de un punto apenas delante de su cara
+const WALL_LIGHT_OFFSET: f32 = 0.05;
+// Color del panel de un tubo fluorescente encendido visto en el techo
+const FIXTURE_COLOR: [u8; 3] = [255, 250, 220];
+
+// Renderizado por software: paredes, suelo y techo se escriben pixel a pixel en una
+// imagen fuera de pantalla que se sube a la GPU como una sola textura por frame.
+pub struct Renderer {
+    width: usize,
+    height: usize,
+    frame: Image,
+    frame_texture: Texture2D,
+    // Distancia perpendicular del suelo/techo visible en cada fila de pantalla (cambia
+    // con el horizonte y la altura de la cámara), y ángulo vertical de cada fila respecto
+    // del centro de la vista (para el haz de la linterna)
+    row_distances: Vec<f32>,
+    row_angles: Vec<f32>,
+    // Distancia perpendicular a la pared de cada columna, para recortar sprites
+    depth_buffer: Vec<f32>,
+    // Intensidad de la linterna en el último cuadro, para iluminar también los sprites
+    flashlight: f32,
+}
+
+impl Renderer {
+    pub fn new(width: f32, height: f32) -> Self {
+        let width = width as usize;
+        let height = height as usize;
+
+        let frame = Image::gen_image_color(width as u16, height as u16, BLACK);
+        let frame_texture = Texture2D::from_image(&frame);
+        frame_texture.set_filter(FilterMode::Nearest);
+
+        let center = height as f32 / 2.0;
+        let focal_length = (width as f32 / 2.0) / (FOV / 2.0).tan();
+        let row_angles = (0..height)
+            .map(|y| ((y as f32 + 0.5 - center) / focal_length).atan())
+            .collect();
+
+        Self {
+            width,
+            height,
+            frame,
+            frame_texture,
+            row_distances: vec![0.0; height],
+            row_angles,
+            depth_buffer: vec![f32::INFINITY; width],
+            flashlight: 0.0,
+        }
+    }
+
+    /// Verdadero si el sprite se vio en el último cuadro: dentro del campo de visión y
+    /// sin paredes delante en al menos una columna
+    pub fn is_visible(&self, sprite: &Sprite, player: &Player) -> bool {
+        is_sprite_visible(sprite, player, &self.depth_buffer, self.height as f32)
+    }
+
+    /// Dibuja los sprites recortados contra las paredes del último cuadro y con su misma luz
+    pub fn draw_sprites(&self, sprites: &mut [Sprite], player: &Player, light_map: &LightMap) {
+        draw_sprites(sprites, player, &self.depth_buffer, self.height as f32, light_map, self.flashlight);
+    }
+
+    /// Dibuja paredes, suelo y techo iluminados por las lámparas del techo y por la
+    /// linterna, con intensidad `flashlight` (0 = apagada)
+    pub fn render_world(
+        &mut self,
+        player: &Player,
+        world_map: &dyn TileMap,
+        texture_manager: &TextureManager,
+        level: &LevelDef,
+        light_map: &LightMap,
+        flashlight: f32,
+    ) {
+        let half_fov = FOV / 2.0;
+        let delta_angle = FOV / self.width as f32;
+        // Mirar arriba o abajo corre el horizonte; el balanceo sube y baja la cámara
+        let horizon = player.horizon(self.height as f32);
+        let eye_height = player.eye_height();
+        let palette = &level.palette;
+        self.flashlight = flashlight;
+        self.update_row_distances(horizon, eye_height);
+
+        let floor = texture_manager.get_image(level.texture("floor"));
+        let ceiling = texture_manager.get_image(level.texture("ceiling"));
+
+        for column in 0..self.width {
+            let ray_angle = column as f32 * delta_angle - half_fov;
+            let angle = player.angle + ray_angle;
+            let hit = cast_ray(world_map, player.x, player.y, angle);
+
+            let corrected_distance = hit.distance * ray_angle.cos();
+            self.depth_buffer[column] = corrected_distance;
+            let wall_height = (self.height as f32 / (corrected_distance + 0.0001)) * WALL_SCALE;
+            let wall_top = horizon - wall_height * (1.0 - eye_height);
+            let wall_bottom = wall_top + wall_height;
+
+            let first_wall_row = wall_top.max(0.0) as usize;
+            let end_wall_row = (wall_bottom.max(0.0) as usize).min(self.height);
+
+            // Vector del rayo escalado para que multiplicarlo por la distancia perpendicular
+            // de una fila dé el punto del suelo que se ve en esta columna
+            let origin = vec2(player.x, player.y);
+            let ray = vec2(angle.cos(), angle.sin()) / ray_angle.cos();
+
+            for y in 0..first_wall_row {
+                let distance = self.row_distances[y];
+                let point = origin + ray * distance;
+                let brightness = self.light_level(light_map.level_at(point.x, point.y), distance, ray_angle, y);
+                // Los tubos se ven como paneles que brillan por sí solos; apagados solo los ilumina lo demás
+                let color = match light_map.fixture_at(point.x, point.y) {
+                    Some(fixture) => shade(FIXTURE_COLOR, fixture.max(brightness * 0.4), palette.light),
+                    None => shade(sample_plane(ceiling, point, palette.ceiling), brightness, palette.light),
+                };
+                self.put_pixel(column, y, color);
+            }
+
+            let wall_image = hit.tile.props().texture.and_then(|name| texture_manager.get_image(level.texture(name)));
+            let wall_point = origin + vec2(angle.cos(), angle.sin()) * (hit.distance - WALL_LIGHT_OFFSET);
+            let wall_light = light_map.level_at(wall_point.x, wall_point.y);
+            for y in first_wall_row..end_wall_row {
+                let v = (y as f32 + 0.5 - wall_top) / wall_height;
+                let brightness = self.light_level(wall_light, corrected_distance, ray_angle, y);
+                let color = wall_color(&hit, wall_image, v, brightness, palette.light);
+                self.put_pixel(column, y, color);
+            }
+
+            for y in end_wall_row..self.height {
+                let distance = self.row_distances[y];
+                let point = origin + ray * distance;
+                let color = sample_plane(floor, point, palette.floor);
+                let brightness = self.light_level(light_map.level_at(point.x, point.y), distance, ray_angle, y);
+                self.put_pixel(column, y, shade(color, brightness, palette.light));
+            }
+        }
+
+        self.frame_texture.update(&self.frame);
+        draw_texture_ex(
+            &self.frame_texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(self.width as f32, self.height as f32)),
+                ..Default::default()
+            },
+        );
+    }
+
+    // Las filas debajo del horizonte ven el suelo, a eye_height de los ojos; las de arriba
+    // ven el techo, al resto de la altura de la pared
+    fn update_row_distances(&mut self, horizon: f32, eye_height: f32) {
+        let scale = WALL_SCALE * self.height as f32;
+        for (y, distance) in self.row_distances.iter_mut().enumerate() {
+            let offset = y as f32 + 0.5 - horizon;
+            let height = if offset > 0.0 { eye_height } else { 1.0 - eye_height };
+            *distance = scale * height / offset.abs().max(0.5);
+        }
+    }
+
+    // Brillo del pixel de la fila `y` en la columna con ángulo `ray_angle`, a `distance`
+    // celdas, con la luz `ambient` de las lámparas en ese punto
+    fn light_level(&self, ambient: f32, distance: f32, ray_angle: f32, y: usize) -> f32 {
+        light_level(ambient, distance, ray_angle.hypot(self.row_angles[y]), self.flashlight)
+    }
+
+    fn put_pixel(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        let idx = (y * self.width + x) * 4;
+        self.frame.bytes[idx] = color[0];
+        self.frame.bytes[idx + 1] = color[1];
+        self.frame.bytes[idx + 2] = color[2];
+        self.frame.bytes[idx + 3] = 255;
+    }
+}
+
+// Color del suelo o techo en el punto del mundo que se ve en un pixel: se muestrea la
+// textura en ese punto; sin textura se usa el color plano de respaldo
+fn sample_plane(image: Option<&Image>, point: Vec2, fallback: [u8; 3]) -> [u8; 3] {
+    match image {
+        Some(image) => sample(image, point.x - point.x.floor(), point.y - point.y.floor()),
+        None => fallback,
+    }
+}
+
+fn wall_color(hit: &RayHit, image: Option<&Image>, v: f32, brightness: f32, light: [f32; 3]) -> [u8; 3] {
+    let base = match image {
+        Some(image) => sample(image, hit.tex_u, v),
+        // Sin textura se usa el color del minimapa
+        None => {
+            let color = hit.tile.props().minimap_color;
+            [(color.r * 255.0) as u8, (color.g * 255.0) as u8, (color.b * 255.0) as u8]
+        }
+    };
+
+    // Oscurecer paredes verticales para dar profundidad
+    let side_factor = if hit.side.is_vertical() { 0.8 } else { 1.0 };
+
+    shade(base, side_factor * brightness, light)
+}
+
+/// Brillo de un punto a `distance` celdas y `angle` radianes del centro de la vista: la
+/// luz `ambient` que le llega de las lámparas (del mapa de luz) más la de la linterna,
+/// con intensidad `flashlight` (0 = apagada)
+pub fn light_level(ambient: f32, distance: f32, angle: f32, flashlight: f32) -> f32 {
+    MIN_AMBIENT + ambient + flashlight * beam(angle, distance)
+}
+
+// Aplica el brillo por distancia y la luz (color) del nivel
+fn shade(color: [u8; 3], factor: f32, light: [f32; 3]) -> [u8; 3] {
+    [
+        (color[0] as f32 * factor * light[0]).min(255.0) as u8,
+        (color[1] as f32 * factor * light[1]).min(255.0) as u8,
+        (color[2] as f32 * factor * light[2]).min(255.0) as u8,
+    ]
+}
+
+// Muestreo nearest con coordenadas normalizadas (0.0..1.0)
+fn sample(image: &Image, u: f32, v: f32) -> [u8; 3] {
+    let width = image.width as usize;
+    let height = image.height as usize;
+    let x = ((u * width as f32) as usize).min(width - 1);
+    let y = ((v * height as f32) as usize).min(height - 1);
+    let idx = (y * width + x) * 4;
+    [image.bytes[idx], image.bytes[idx + 1], image.bytes[idx + 2]]
+}
@@ -0,0 +1,271 @@
+// Opciones del jugador que se guardan entre partidas, en un archivo de texto con el
+// mismo formato de "clave: valor" que el encabezado de los niveles:
+//
+//     # Configuración de Backrooms
+//     look_pitch: on
+//     head_bob: off
+//     mouse_sensitivity: 1.5
+//     mouse_smoothing: 0.04
+//     invert_x: off
+//     invert_y: on
+//
+// Las claves que faltan o no se entienden toman su valor por defecto.
+
+pub const SETTINGS_FILE: &str = "settings.txt";
+
+// Rango y paso de la sensibilidad del mouse (1.0 = la de siempre)
+const SENSITIVITY_RANGE: (f32, f32) = (0.1, 4.0);
+const SENSITIVITY_STEP: f32 = 0.1;
+// Rango y paso del suavizado del mouse, en segundos (0 = sin suavizado)
+const SMOOTHING_RANGE: (f32, f32) = (0.0, 0.2);
+const SMOOTHING_STEP: f32 = 0.02;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// Mirar arriba y abajo con el mouse
+    pub look_pitch: bool,
+    /// Balanceo de la cámara al caminar
+    pub head_bob: bool,
+    /// Multiplica lo que gira la vista por cada pixel que se mueve el mouse
+    pub mouse_sensitivity: f32,
+    /// Segundos que tarda la vista en alcanzar (casi dos tercios de) lo que se movió el mouse
+    pub mouse_smoothing: f32,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            look_pitch: true,
+            head_bob: true,
+            mouse_sensitivity: 1.0,
+            mouse_smoothing: 0.0,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+}
+
+/// Opciones que se pueden cambiar en la pantalla de opciones, en el orden en que aparecen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingsOption {
+    LookPitch,
+    HeadBob,
+    MouseSensitivity,
+    MouseSmoothing,
+    InvertX,
+    InvertY,
+}
+
+impl SettingsOption {
+    pub const ALL: [SettingsOption; 6] = [
+        SettingsOption::LookPitch,
+        SettingsOption::HeadBob,
+        SettingsOption::MouseSensitivity,
+        SettingsOption::MouseSmoothing,
+        SettingsOption::InvertX,
+        SettingsOption::InvertY,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingsOption::LookPitch => "Look up/down",
+            SettingsOption::HeadBob => "Head bob",
+            SettingsOption::MouseSensitivity => "Mouse sensitivity",
+            SettingsOption::MouseSmoothing => "Mouse smoothing",
+            SettingsOption::InvertX => "Invert mouse X",
+            SettingsOption::InvertY => "Invert mouse Y",
+        }
+    }
+
+    // Clave en el archivo de configuración
+    fn key(self) -> &'static str {
+        match self {
+            SettingsOption::LookPitch => "look_pitch",
+            SettingsOption::HeadBob => "head_bob",
+            SettingsOption::MouseSensitivity => "mouse_sensitivity",
+            SettingsOption::MouseSmoothing => "mouse_smoothing",
+            SettingsOption::InvertX => "invert_x",
+            SettingsOption::InvertY => "invert_y",
+        }
+    }
+}
+
+impl Settings {
+    /// Lee la configuración guardada; si no hay archivo usa la de por defecto
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => parse_settings(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, serialize_settings(self))
+    }
+
+    /// Valor de la opción como se muestra en pantalla
+    pub fn value_text(&self, option: SettingsOption) -> String {
+        match option {
+            SettingsOption::MouseSensitivity => format!("{:.1}", self.mouse_sensitivity),
+            SettingsOption::MouseSmoothing if self.mouse_smoothing <= 0.0 => String::from("off"),
+            SettingsOption::MouseSmoothing => format!("{:.2}s", self.mouse_smoothing),
+            _ => String::from(switch_name(self.switch(option).copied().unwrap_or(false))),
+        }
+    }
+
+    /// Sube (step = 1) o baja (step = -1) un valor numérico dentro de su rango; las
+    /// opciones de sí/no cambian con cualquier paso
+    pub fn adjust(&mut self, option: SettingsOption, step: i32) {
+        match option {
+            SettingsOption::MouseSensitivity => {
+                self.mouse_sensitivity = stepped(self.mouse_sensitivity, step, SENSITIVITY_STEP, SENSITIVITY_RANGE);
+            }
+            SettingsOption::MouseSmoothing => {
+                self.mouse_smoothing = stepped(self.mouse_smoothing, step, SMOOTHING_STEP, SMOOTHING_RANGE);
+            }
+            _ => {
+                if let Some(on) = self.switch_mut(option) {
+                    *on = !*on;
+                }
+            }
+        }
+    }
+
+    // La opción de sí/no correspondiente, o None si la opción es numérica
+    fn switch(&self, option: SettingsOption) -> Option<&bool> {
+        match option {
+            SettingsOption::LookPitch => Some(&self.look_pitch),
+            SettingsOption::HeadBob => Some(&self.head_bob),
+            SettingsOption::InvertX => Some(&self.invert_x),
+            SettingsOption::InvertY => Some(&self.invert_y),
+            SettingsOption::MouseSensitivity | SettingsOption::MouseSmoothing => None,
+        }
+    }
+
+    fn switch_mut(&mut self, option: SettingsOption) -> Option<&mut bool> {
+        match option {
+            SettingsOption::LookPitch => Some(&mut self.look_pitch),
+            SettingsOption::HeadBob => Some(&mut self.head_bob),
+            SettingsOption::InvertX => Some(&mut self.invert_x),
+            SettingsOption::InvertY => Some(&mut self.invert_y),
+            SettingsOption::MouseSensitivity | SettingsOption::MouseSmoothing => None,
+        }
+    }
+
+    // Guarda el valor de texto de la opción; None si no es válido para ella
+    fn set_value(&mut self, option: SettingsOption, value: &str) -> Option<()> {
+        match option {
+            SettingsOption::MouseSensitivity => {
+                let value: f32 = value.parse().ok()?;
+                self.mouse_sensitivity = value.clamp(SENSITIVITY_RANGE.0, SENSITIVITY_RANGE.1);
+            }
+            SettingsOption::MouseSmoothing => {
+                let value: f32 = value.parse().ok()?;
+                self.mouse_smoothing = value.clamp(SMOOTHING_RANGE.0, SMOOTHING_RANGE.1);
+            }
+            _ => *self.switch_mut(option)? = parse_switch(value)?,
+        }
+        Some(())
+    }
+}
+
+pub fn parse_settings(text: &str) -> Settings {
+    let mut settings = Settings::default();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            println!("✗ Configuración, línea {}: se esperaba \"clave: valor\"", index + 1);
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let Some(option) = SettingsOption::ALL.into_iter().find(|option| option.key() == key) else {
+            println!("✗ Configuración, línea {}: clave desconocida \"{}\"", index + 1, key);
+            continue;
+        };
+        if settings.set_value(option, value).is_none() {
+            println!("✗ Configuración, línea {}: valor no válido para {}: \"{}\"", index + 1, key, value);
+        }
+    }
+
+    settings
+}
+
+pub fn serialize_settings(settings: &Settings) -> String {
+    let mut text = String::from("# Configuración de Backrooms\n");
+    for option in SettingsOption::ALL {
+        let value = match option {
+            SettingsOption::MouseSensitivity => settings.mouse_sensitivity.to_string(),
+            SettingsOption::MouseSmoothing => settings.mouse_smoothing.to_string(),
+            _ => settings.value_text(option),
+        };
+        text.push_str(&format!("{}: {}\n", option.key(), value));
+    }
+    text
+}
+
+// Valor que se mueve `step` pasos dentro del rango, redondeado al paso para no acumular error
+fn stepped(value: f32, step: i32, size: f32, (min, max): (f32, f32)) -> f32 {
+    let steps = (value / size).round() + step as f32;
+    (steps * size).clamp(min, max)
+}
+
+fn parse_switch(value: &str) -> Option<bool> {
+    match value {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn switch_name(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_falls_back_to_defaults() {
+        let settings = Settings {
+            look_pitch: false,
+            head_bob: true,
+            mouse_sensitivity: 2.3,
+            mouse_smoothing: 0.06,
+            invert_x: false,
+            invert_y: true,
+        };
+        assert_eq!(parse_settings(&serialize_settings(&settings)), settings);
+
+        // Lo que no se entiende queda con su valor por defecto
+        let parsed = parse_settings("head_bob: off\nlook_pitch: maybe\nvolume: 3\nbasura\nmouse_sensitivity: 99\n");
+        assert_eq!(
+            parsed,
+            Settings { head_bob: false, mouse_sensitivity: SENSITIVITY_RANGE.1, ..Settings::default() }
+        );
+    }
+
+    #[test]
+    fn adjusting_stays_in_range() {
+        let mut settings = Settings::default();
+        for _ in 0..100 {
+            settings.adjust(SettingsOption::MouseSensitivity, -1);
+        }
+        assert_eq!(settings.mouse_sensitivity, SENSITIVITY_RANGE.0);
+
+        settings.adjust(SettingsOption::MouseSmoothing, -1);
+        assert_eq!(settings.value_text(SettingsOption::MouseSmoothing), "off");
+        settings.adjust(SettingsOption::MouseSmoothing, 1);
+        assert_eq!(settings.value_text(SettingsOption::MouseSmoothing), "0.02s");
+
+        settings.adjust(SettingsOption::InvertY, -1);
+        assert!(settings.invert_y);
+    }
+}
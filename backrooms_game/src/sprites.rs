@@ -0,0 +1,219 @@
+use macroquad::prelude::*;
+use crate::lighting::LightMap;
+use crate::player::Player;
+use crate::renderer::{light_level, FOV, WALL_SCALE};
+
+// Sprite tipo billboard: siempre mira a la cámara y se apoya en el suelo
+pub struct Sprite<'a> {
+    pub x: f32,
+    pub y: f32,
+    pub texture: &'a Texture2D,
+    /// Altura en el mundo (1.0 = altura de una pared)
+    pub height: f32,
+    pub tint: Color,
+}
+
+/// Dibuja los sprites del más lejano al más cercano, recortando columna por columna
+/// contra el buffer de profundidad que dejó el renderizado de paredes. Se iluminan igual
+/// que las paredes: con el mapa de luz y con la linterna de intensidad `flashlight`.
+pub fn draw_sprites(sprites: &mut [Sprite], player: &Player, depth_buffer: &[f32], screen_height: f32, light_map: &LightMap, flashlight: f32) {
+    let distance_sq = |sprite: &Sprite| (sprite.x - player.x).powi(2) + (sprite.y - player.y).powi(2);
+    sprites.sort_by(|a, b| distance_sq(b).total_cmp(&distance_sq(a)));
+
+    for sprite in sprites.iter() {
+        let ambient = light_map.level_at(sprite.x, sprite.y);
+        draw_sprite(sprite, player, depth_buffer, screen_height, ambient, flashlight);
+    }
+}
+
+/// Verdadero si alguna columna del sprite queda en pantalla sin una pared delante
+pub fn is_sprite_visible(sprite: &Sprite, player: &Player, depth_buffer: &[f32], screen_height: f32) -> bool {
+    project(sprite, player, depth_buffer.len(), screen_height).is_some_and(|projection| projection.is_visible(depth_buffer))
+}
+
+// Dónde cae un sprite en pantalla
+struct Projection {
+    depth: f32,
+    // Ángulo respecto del centro de la vista
+    angle: f32,
+    left: f32,
+    width: f32,
+    height: f32,
+    top_y: f32,
+    first_column: usize,
+    end_column: usize,
+}
+
+impl Projection {
+    fn columns(&self) -> std::ops::Range<usize> {
+        self.first_column..self.end_column
+    }
+
+    fn is_visible(&self, depth_buffer: &[f32]) -> bool {
+        self.columns().any(|column| self.depth < depth_buffer[column])
+    }
+}
+
+// None si el sprite está detrás de la cámara
+fn project(sprite: &Sprite, player: &Player, columns: usize, screen_height: f32) -> Option<Projection> {
+    let aspect = sprite.texture.width() / sprite.texture.height();
+    project_billboard((sprite.x, sprite.y), sprite.height, aspect, player, columns, screen_height)
+}
+
+// Proyección de un billboard de la altura y proporción (ancho / alto) dadas, apoyado en (x, y)
+fn project_billboard(
+    (x, y): (f32, f32),
+    sprite_height: f32,
+    aspect: f32,
+    player: &Player,
+    columns: usize,
+    screen_height: f32,
+) -> Option<Projection> {
+    let dx = x - player.x;
+    let dy = y - player.y;
+
+    // Ángulo relativo a la dirección de la cámara, normalizado a [-PI, PI]
+    let mut relative_angle = dy.atan2(dx) - player.angle;
+    while relative_angle > std::f32::consts::PI {
+        relative_angle -= 2.0 * std::f32::consts::PI;
+    }
+    while relative_angle < -std::f32::consts::PI {
+        relative_angle += 2.0 * std::f32::consts::PI;
+    }
+
+    // Misma corrección de ojo de pez que usan las paredes
+    let depth = (dx * dx + dy * dy).sqrt() * relative_angle.cos();
+    if depth <= 0.1 {
+        return None;
+    }
+
+    let delta_angle = FOV / columns as f32;
+    let center_column = (relative_angle + FOV / 2.0) / delta_angle;
+
+    let projected_height = screen_height / depth * WALL_SCALE;
+    let height = projected_height * sprite_height;
+    let width = height * aspect;
+    let floor_y = player.horizon(screen_height) + projected_height * player.eye_height();
+
+    let left = center_column - width / 2.0;
+    Some(Projection {
+        depth,
+        angle: relative_angle,
+        left,
+        width,
+        height,
+        top_y: floor_y - height,
+        first_column: left.max(0.0) as usize,
+        end_column: ((center_column + width / 2.0).max(0.0) as usize).min(columns),
+    })
+}
+
+fn draw_sprite(sprite: &Sprite, player: &Player, depth_buffer: &[f32], screen_height: f32, ambient: f32, flashlight: f32) {
+    let Some(projection) = project(sprite, player, depth_buffer.len(), screen_height) else {
+        return;
+    };
+    let Projection { depth, angle, left, width: sprite_width, height: sprite_height, top_y, first_column, end_column } = projection;
+
+    // Misma luz que paredes y suelo: la de las lámparas donde está parado más la linterna
+    let brightness = light_level(ambient, depth, angle, flashlight).min(1.0);
+    let tint = Color::new(
+        sprite.tint.r * brightness,
+        sprite.tint.g * brightness,
+        sprite.tint.b * brightness,
+        sprite.tint.a,
+    );
+
+    // Agrupar columnas visibles contiguas para dibujarlas con una sola llamada
+    let mut column = first_column;
+    while column < end_column {
+        if depth >= depth_buffer[column] {
+            column += 1;
+            continue;
+        }
+
+        let run_start = column;
+        while column < end_column && depth < depth_buffer[column] {
+            column += 1;
+        }
+
+        let tex_width = sprite.texture.width();
+        let u_start = (run_start as f32 - left) / sprite_width;
+        let u_end = (column as f32 - left) / sprite_width;
+
+        draw_texture_ex(
+            sprite.texture,
+            run_start as f32,
+            top_y,
+            tint,
+            DrawTextureParams {
+                dest_size: Some(vec2((column - run_start) as f32, sprite_height)),
+                source: Some(Rect::new(
+                    u_start * tex_width,
+                    0.0,
+                    (u_end - u_start) * tex_width,
+                    sprite.texture.height(),
+                )),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: usize = 120;
+    const SCREEN_HEIGHT: f32 = 600.0;
+
+    // Jugador en (0, 0) mirando hacia +x, con las paredes que deja el buffer de profundidad dado
+    fn visible(x: f32, y: f32, depth_buffer: &[f32]) -> bool {
+        let player = Player::new(0.0, 0.0, 0.0);
+        project_billboard((x, y), 0.5, 1.0, &player, depth_buffer.len(), SCREEN_HEIGHT)
+            .is_some_and(|projection| projection.is_visible(depth_buffer))
+    }
+
+    #[test]
+    fn visible_only_inside_the_field_of_view() {
+        let open = [f32::INFINITY; COLUMNS];
+        assert!(visible(5.0, 0.0, &open));
+        assert!(visible(5.0, 2.0, &open));
+        // A 90° a un lado y detrás de la cámara no se ve
+        assert!(!visible(0.0, 5.0, &open));
+        assert!(!visible(-5.0, 0.0, &open));
+        assert!(!visible(-5.0, 0.3, &open));
+    }
+
+    #[test]
+    fn walls_in_front_hide_sprites() {
+        let wall_near = [2.0; COLUMNS];
+        assert!(!visible(5.0, 0.0, &wall_near));
+        assert!(visible(1.5, 0.0, &wall_near));
+    }
+
+    #[test]
+    fn partly_hidden_sprites_are_visible() {
+        let player = Player::new(0.0, 0.0, 0.0);
+        let projection = project_billboard((5.0, 0.0), 0.5, 1.0, &player, COLUMNS, SCREEN_HEIGHT).unwrap();
+        assert!(projection.columns().len() > 1);
+
+        // Pared delante de todas sus columnas menos la última
+        let mut depth_buffer = [f32::INFINITY; COLUMNS];
+        depth_buffer[projection.first_column..projection.end_column - 1].fill(2.0);
+        assert!(visible(5.0, 0.0, &depth_buffer));
+
+        depth_buffer[projection.end_column - 1] = 2.0;
+        assert!(!visible(5.0, 0.0, &depth_buffer));
+    }
+
+    #[test]
+    fn sprites_at_the_screen_edge_are_clipped() {
+        // Medio afuera del borde derecho del campo de visión: algunas columnas quedan en pantalla
+        let edge = (FOV / 2.0).tan() * 5.0;
+        let player = Player::new(0.0, 0.0, 0.0);
+        let projection = project_billboard((5.0, edge), 0.5, 1.0, &player, COLUMNS, SCREEN_HEIGHT).unwrap();
+        assert!(projection.end_column <= COLUMNS);
+        assert!(projection.first_column < projection.end_column);
+        assert!(visible(5.0, edge, &[f32::INFINITY; COLUMNS]));
+    }
+}
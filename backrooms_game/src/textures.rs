@@ -3,17 +3,27 @@ use std::collections::HashMap;
 
 pub struct TextureManager {
     pub textures: HashMap<String, Texture2D>,
+    // Copia en CPU de cada textura para muestrear pixeles en el renderizado por software
+    pub images: HashMap<String, Image>,
 }
 
 impl TextureManager {
     pub fn new() -> Self {
         Self {
             textures: HashMap::new(),
+            images: HashMap::new(),
         }
     }
     
-    pub fn add_texture(&mut self, name: String, texture: Texture2D) {
-        self.textures.insert(name, texture);
+    pub fn add_texture(&mut self, name: String, image: Image) {
+        let texture = Texture2D::from_image(&image);
+        texture.set_filter(FilterMode::Nearest);
+        self.textures.insert(name.clone(), texture);
+        self.images.insert(name, image);
+    }
+    
+    pub fn get_image(&self, name: &str) -> Option<&Image> {
+        self.images.get(name)
     }
 }
 
@@ -33,10 +43,55 @@ pub async fn load_textures() -> TextureManager {
     let floor_texture = generate_floor_texture();
     texture_manager.add_texture("floor".to_string(), floor_texture);
     
+    let ceiling_texture = match load_ceiling_image().await {
+        Some(image) => image,
+        None => generate_ceiling_texture(),
+    };
+    texture_manager.add_texture("ceiling".to_string(), ceiling_texture);
+    
+    // Texturas propias de los otros niveles de la campaña
+    texture_manager.add_texture("garage_wall".to_string(), generate_garage_wall_texture());
+    texture_manager.add_texture("garage_floor".to_string(), generate_garage_floor_texture());
+    texture_manager.add_texture("garage_ceiling".to_string(), generate_garage_ceiling_texture());
+    texture_manager.add_texture("pipes_wall".to_string(), generate_pipes_wall_texture());
+    texture_manager.add_texture("pipes_floor".to_string(), generate_pipes_floor_texture());
+    texture_manager.add_texture("pipes_ceiling".to_string(), generate_pipes_ceiling_texture());
+    
+    // Objetos que se dibujan como sprites sobre el suelo
+    texture_manager.add_texture("battery".to_string(), generate_battery_texture());
+    
     texture_manager
 }
 
-fn generate_brick_wall_texture() -> Texture2D {
+async fn load_ceiling_image() -> Option<Image> {
+    println!("Intentando cargar textura del techo...");
+    
+    // El archivo se llama "celling.png" en los assets
+    let paths = [
+        "assets/textures/celling.png",
+        "./assets/textures/celling.png",
+        "../assets/textures/celling.png",
+        "celling.png",
+    ];
+    
+    for path in paths {
+        if std::path::Path::new(path).exists() {
+            match load_image(path).await {
+                Ok(image) => {
+                    println!("✓ Textura del techo cargada: {}", path);
+                    return Some(image);
+                }
+                Err(e) => println!("✗ Error cargando {}: {}", path, e),
+            }
+        }
+    }
+    
+    println!("  ✗ No se pudo encontrar celling.png en ninguna ubicación");
+    println!("  → Se usará un techo generado por código");
+    None
+}
+
+fn generate_brick_wall_texture() -> Image {
     const SIZE: usize = 128;
     let mut pixels = vec![0u8; SIZE * SIZE * 4];
     
@@ -102,16 +157,14 @@ fn generate_brick_wall_texture() -> Texture2D {
         }
     }
     
-    let image = Image {
+    Image {
         bytes: pixels,
         width: SIZE as u16,
         height: SIZE as u16,
-    };
-    
-    Texture2D::from_image(&image)
+    }
 }
 
-fn generate_bloody_brick_wall_texture() -> Texture2D {
+fn generate_bloody_brick_wall_texture() -> Image {
     const SIZE: usize = 128;
     let mut pixels = vec![0u8; SIZE * SIZE * 4];
     
@@ -163,13 +216,11 @@ fn generate_bloody_brick_wall_texture() -> Texture2D {
     add_blood_stains(&mut pixels, SIZE);
     add_help_messages(&mut pixels, SIZE);
     
-    let image = Image {
+    Image {
         bytes: pixels,
         width: SIZE as u16,
         height: SIZE as u16,
-    };
-    
-    Texture2D::from_image(&image)
+    }
 }
 
 fn add_blood_stains(pixels: &mut Vec<u8>, size: usize) {
@@ -446,7 +497,7 @@ fn draw_letter_pattern(pixels: &mut Vec<u8>, size: usize, pattern: &Vec<Vec<bool
     }
 }
 
-fn generate_exit_texture() -> Texture2D {
+fn generate_exit_texture() -> Image {
     const SIZE: usize = 64;
     let mut pixels = vec![0u8; SIZE * SIZE * 4];
     
@@ -470,16 +521,14 @@ fn generate_exit_texture() -> Texture2D {
         }
     }
     
-    let image = Image {
+    Image {
         bytes: pixels,
         width: SIZE as u16,
         height: SIZE as u16,
-    };
-    
-    Texture2D::from_image(&image)
+    }
 }
 
-fn generate_floor_texture() -> Texture2D {
+fn generate_floor_texture() -> Image {
     const SIZE: usize = 64;
     let mut pixels = vec![0u8; SIZE * SIZE * 4];
     
@@ -499,11 +548,240 @@ fn generate_floor_texture() -> Texture2D {
         }
     }
     
-    let image = Image {
+    Image {
+        bytes: pixels,
+        width: SIZE as u16,
+        height: SIZE as u16,
+    }
+}
+
+fn generate_ceiling_texture() -> Image {
+    const SIZE: usize = 64;
+    let mut pixels = vec![0u8; SIZE * SIZE * 4];
+    
+    // Placas de techo falso con una lámpara fluorescente en el centro
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let idx = (y * SIZE + x) * 4;
+            
+            let is_seam = x % 32 == 0 || y % 32 == 0;
+            let is_light = (12..20).contains(&(x % 32)) && (4..28).contains(&(y % 32));
+            let noise = ((x * 7 + y * 11) % 6) as u8;
+            
+            let (r, g, b) = if is_seam {
+                (110, 105, 70)
+            } else if is_light {
+                (250, 245, 210)
+            } else {
+                (170 + noise, 160 + noise, 110 + noise / 2)
+            };
+            
+            pixels[idx] = r;
+            pixels[idx + 1] = g;
+            pixels[idx + 2] = b;
+            pixels[idx + 3] = 255;
+        }
+    }
+    
+    Image {
         bytes: pixels,
         width: SIZE as u16,
         height: SIZE as u16,
+    }
+}
+
+// Crea una textura cuadrada de SIZE pixeles con el color que devuelve `color(x, y)`
+fn generate_pattern_texture(size: usize, color: impl Fn(usize, usize) -> (u8, u8, u8)) -> Image {
+    let mut pixels = vec![0u8; size * size * 4];
+    
+    for y in 0..size {
+        for x in 0..size {
+            let idx = (y * size + x) * 4;
+            let (r, g, b) = color(x, y);
+            pixels[idx] = r;
+            pixels[idx + 1] = g;
+            pixels[idx + 2] = b;
+            pixels[idx + 3] = 255;
+        }
+    }
+    
+    Image {
+        bytes: pixels,
+        width: size as u16,
+        height: size as u16,
+    }
+}
+
+// Nivel 1: concreto con juntas de encofrado y una franja amarilla y negra abajo
+fn generate_garage_wall_texture() -> Image {
+    generate_pattern_texture(64, |x, y| {
+        let noise = ((x * 13 + y * 7) % 12) as u8;
+        if (52..60).contains(&y) {
+            if (x + y) / 6 % 2 == 0 { (200, 170, 40) } else { (25, 25, 25) }
+        } else if y % 16 == 0 || x % 32 == 0 {
+            (90, 90, 92)
+        } else {
+            (125 + noise, 125 + noise, 128 + noise)
+        }
+    })
+}
+
+// Asfalto con la línea blanca de un cajón de estacionamiento
+fn generate_garage_floor_texture() -> Image {
+    generate_pattern_texture(64, |x, y| {
+        let noise = ((x * 17 + y * 31) % 14) as u8;
+        if (2..5).contains(&x) && y % 32 < 24 {
+            (190, 190, 180)
+        } else {
+            (45 + noise, 45 + noise, 48 + noise)
+        }
+    })
+}
+
+// Losa de concreto con vigas y un tubo fluorescente apagado
+fn generate_garage_ceiling_texture() -> Image {
+    generate_pattern_texture(64, |x, y| {
+        let noise = ((x * 5 + y * 11) % 8) as u8;
+        if y % 32 < 4 {
+            (60, 60, 62)
+        } else if (28..36).contains(&x) && (12..24).contains(&y) {
+            (150, 155, 150)
+        } else {
+            (85 + noise, 85 + noise, 88 + noise)
+        }
+    })
+}
+
+// Nivel 2: tuberías oxidadas verticales sobre una pared de metal
+fn generate_pipes_wall_texture() -> Image {
+    generate_pattern_texture(64, |x, y| {
+        let noise = ((x * 11 + y * 19) % 16) as u8;
+        let pipe_x = x % 16;
+        if (3..12).contains(&pipe_x) {
+            // Sombreado cilíndrico: más claro en el centro del tubo
+            let center = 7.5 - pipe_x as f32;
+            let light = (1.0 - center.abs() / 5.0).max(0.3);
+            let rust = if (y * 3 + x) % 23 < 4 { 40 } else { 0 };
+            (
+                ((150 + rust) as f32 * light) as u8,
+                ((85 + rust / 2) as f32 * light) as u8,
+                (55.0 * light) as u8,
+            )
+        } else if y % 20 < 2 {
+            (50, 40, 35)
+        } else {
+            (70 + noise, 60 + noise, 50 + noise / 2)
+        }
+    })
+}
+
+// Rejilla metálica con fondo oscuro
+fn generate_pipes_floor_texture() -> Image {
+    generate_pattern_texture(64, |x, y| {
+        if x % 8 < 2 || y % 8 < 2 {
+            (110, 95, 80)
+        } else {
+            let noise = ((x * 7 + y * 3) % 6) as u8;
+            (20 + noise, 15 + noise, 12)
+        }
+    })
+}
+
+// Techo oscuro cruzado por tuberías horizontales
+fn generate_pipes_ceiling_texture() -> Image {
+    generate_pattern_texture(64, |x, y| {
+        let pipe_y = y % 32;
+        if (6..14).contains(&pipe_y) {
+            let center = 9.5 - pipe_y as f32;
+            let light = (1.0 - center.abs() / 4.5).max(0.3);
+            ((130.0 * light) as u8, (90.0 * light) as u8, (60.0 * light) as u8)
+        } else {
+            let noise = ((x * 13 + y * 5) % 10) as u8;
+            (35 + noise, 28 + noise, 22 + noise)
+        }
+    })
+}
+
+// Batería tipo pila parada, con fondo transparente para dibujarla como sprite
+fn generate_battery_texture() -> Image {
+    const WIDTH: usize = 16;
+    const HEIGHT: usize = 32;
+    let mut pixels = vec![0u8; WIDTH * HEIGHT * 4];
+    
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let color = match (x, y) {
+                // Polo positivo arriba
+                (6..=9, 4..=6) => (200, 200, 190),
+                (3..=12, 7..=30) => {
+                    // Sombreado cilíndrico y una franja de carga verde
+                    let light = 1.0 - (x as f32 - 7.5).abs() / 9.0;
+                    let (r, g, b) = if (12..=16).contains(&y) { (60, 200, 80) } else { (40, 40, 45) };
+                    ((r as f32 * light) as u8, (g as f32 * light) as u8, (b as f32 * light) as u8)
+                }
+                _ => continue,
+            };
+            let idx = (y * WIDTH + x) * 4;
+            pixels[idx] = color.0;
+            pixels[idx + 1] = color.1;
+            pixels[idx + 2] = color.2;
+            pixels[idx + 3] = 255;
+        }
+    }
+    
+    Image {
+        bytes: pixels,
+        width: WIDTH as u16,
+        height: HEIGHT as u16,
+    }
+}
+
+// Enemigo generado por código para cuando no se encuentra enemigo.png
+pub fn generate_enemy_texture() -> Image {
+    const SIZE: usize = 128;
+    let mut pixels = vec![0u8; SIZE * SIZE * 4];
+    
+    let set = |pixels: &mut Vec<u8>, x: usize, y: usize, r: u8, g: u8, b: u8| {
+        let idx = (y * SIZE + x) * 4;
+        pixels[idx] = r;
+        pixels[idx + 1] = g;
+        pixels[idx + 2] = b;
+        pixels[idx + 3] = 255;
     };
     
-    Texture2D::from_image(&image)
-}
\ No newline at end of file
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let fx = x as f32 / SIZE as f32;
+            let fy = y as f32 / SIZE as f32;
+            
+            // Cuerpo principal (sombra oscura)
+            let body = ((fx - 0.5) / 0.42).powi(2) + ((fy - 0.55) / 0.45).powi(2);
+            if body > 1.0 {
+                continue;
+            }
+            set(&mut pixels, x, y, 10, 10, 10);
+            
+            // Ojos rojos brillantes con pupilas claras
+            for eye_x in [0.36, 0.64] {
+                let eye = ((fx - eye_x).powi(2) + (fy - 0.4).powi(2)).sqrt();
+                if eye < 0.025 {
+                    set(&mut pixels, x, y, 255, 255, 200);
+                } else if eye < 0.08 {
+                    set(&mut pixels, x, y, 230, 30, 30);
+                }
+            }
+            
+            // Boca amenazante
+            let mouth = ((fx - 0.5) / 0.18).powi(2) + ((fy - 0.68) / 0.08).powi(2);
+            if mouth < 1.0 {
+                set(&mut pixels, x, y, 80, 0, 0);
+            }
+        }
+    }
+    
+    Image {
+        bytes: pixels,
+        width: SIZE as u16,
+        height: SIZE as u16,
+    }
+}
@@ -0,0 +1,141 @@
+use macroquad::prelude::Color;
+
+// Tipos de celda del mapa. Para agregar uno nuevo basta con la variante, sus
+// propiedades en Tile::props y agregarla a Tile::ALL.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tile {
+    Empty,
+    Wall,
+    BloodyWall,
+    Exit,
+    Battery,
+}
+
+/// Qué pasa cuando el jugador pisa la celda
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileTrigger {
+    None,
+    Exit,
+    /// Recarga la linterna y la celda queda vacía
+    Battery,
+}
+
+pub struct TileProps {
+    pub solid_for_player: bool,
+    pub solid_for_enemy: bool,
+    /// Detiene los rayos: se dibuja como bloque y tapa la visión
+    pub blocks_sight: bool,
+    /// Nombre en el TextureManager de la textura de sus caras
+    pub texture: Option<&'static str>,
+    pub minimap_color: Color,
+    pub trigger: TileTrigger,
+    /// Nombre en el TextureManager de un objeto que se dibuja como sprite en el centro de la celda
+    pub sprite: Option<&'static str>,
+    /// Carácter que la representa en los archivos de nivel
+    pub symbol: char,
+}
+
+// Propiedades de cada tipo de celda; Tile::props elige la que corresponde
+static EMPTY: TileProps = TileProps {
+    solid_for_player: false,
+    solid_for_enemy: false,
+    blocks_sight: false,
+    texture: None,
+    minimap_color: Color::from_rgba(40, 40, 25, 255),
+    trigger: TileTrigger::None,
+    sprite: None,
+    symbol: '.',
+};
+
+static WALL: TileProps = TileProps {
+    solid_for_player: true,
+    solid_for_enemy: true,
+    blocks_sight: true,
+    texture: Some("wall"),
+    minimap_color: Color::from_rgba(200, 190, 120, 255),
+    trigger: TileTrigger::None,
+    sprite: None,
+    symbol: '#',
+};
+
+static BLOODY_WALL: TileProps = TileProps {
+    solid_for_player: true,
+    solid_for_enemy: true,
+    blocks_sight: true,
+    texture: Some("bloody_wall"),
+    minimap_color: Color::from_rgba(140, 60, 60, 255),
+    trigger: TileTrigger::None,
+    sprite: None,
+    symbol: '%',
+};
+
+// Se ve como un bloque, pero se puede atravesar
+static EXIT: TileProps = TileProps {
+    solid_for_player: false,
+    solid_for_enemy: false,
+    blocks_sight: true,
+    texture: Some("exit"),
+    minimap_color: Color::from_rgba(60, 220, 60, 255),
+    trigger: TileTrigger::Exit,
+    sprite: None,
+    symbol: 'X',
+};
+
+// Celda vacía con una batería en el suelo
+static BATTERY: TileProps = TileProps {
+    solid_for_player: false,
+    solid_for_enemy: false,
+    blocks_sight: false,
+    texture: None,
+    minimap_color: Color::from_rgba(80, 170, 255, 255),
+    trigger: TileTrigger::Battery,
+    sprite: Some("battery"),
+    symbol: 'B',
+};
+
+impl Tile {
+    pub const ALL: [Tile; 5] = [Tile::Empty, Tile::Wall, Tile::BloodyWall, Tile::Exit, Tile::Battery];
+
+    pub fn props(self) -> &'static TileProps {
+        match self {
+            Tile::Empty => &EMPTY,
+            Tile::Wall => &WALL,
+            Tile::BloodyWall => &BLOODY_WALL,
+            Tile::Exit => &EXIT,
+            Tile::Battery => &BATTERY,
+        }
+    }
+
+    pub fn is_solid_for_player(self) -> bool {
+        self.props().solid_for_player
+    }
+
+    pub fn is_solid_for_enemy(self) -> bool {
+        self.props().solid_for_enemy
+    }
+
+    pub fn blocks_sight(self) -> bool {
+        self.props().blocks_sight
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Tile> {
+        Tile::ALL.into_iter().find(|tile| tile.props().symbol == symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_tile_round_trips_through_its_symbol() {
+        for tile in Tile::ALL {
+            // Sin comodín: una variante nueva no compila hasta sumarla aquí y a Tile::ALL
+            match tile {
+                Tile::Empty | Tile::Wall | Tile::BloodyWall | Tile::Exit | Tile::Battery => {}
+            }
+            assert_eq!(Tile::from_symbol(tile.props().symbol), Some(tile));
+        }
+        assert_eq!(Tile::ALL.len(), 5, "Tile::ALL no tiene todas las variantes");
+    }
+}
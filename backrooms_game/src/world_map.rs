@@ -0,0 +1,90 @@
+// Mapa del mundo con tamaño definido en tiempo de ejecución.
+// Las celdas se guardan fila por fila en un solo vector.
+
+use crate::tile::Tile;
+
+/// Consulta de celdas que comparten el mapa fijo y el mundo infinito por chunks.
+/// Raycaster, colisiones, enemigo y minimapa solo ven el mundo a través de esta interfaz.
+pub trait TileMap {
+    /// Celda en (x, y), o None fuera del mapa o en una zona que no está cargada
+    fn tile(&self, x: i32, y: i32) -> Option<Tile>;
+
+    /// Celda que contiene el punto del mundo (x, y)
+    fn tile_at(&self, x: f32, y: f32) -> Option<Tile> {
+        self.tile(x.floor() as i32, y.floor() as i32)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct WorldMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl WorldMap {
+    pub fn new(width: usize, height: usize, fill: Tile) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![fill; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Celda en (x, y), o None si queda fuera del mapa
+    pub fn get(&self, x: usize, y: usize) -> Option<Tile> {
+        if self.in_bounds(x, y) {
+            Some(self.tiles[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Cambia la celda en (x, y). Escribir fuera del mapa es un error de programación.
+    pub fn set(&mut self, x: usize, y: usize, tile: Tile) {
+        assert!(
+            self.in_bounds(x, y),
+            "celda ({}, {}) fuera del mapa {}x{}",
+            x, y, self.width, self.height
+        );
+        self.tiles[y * self.width + x] = tile;
+    }
+
+    pub fn fill(&mut self, tile: Tile) {
+        self.tiles.fill(tile);
+    }
+
+    /// Mapa de prueba a partir de filas de símbolos, como en los archivos de nivel
+    #[cfg(test)]
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let mut map = Self::new(rows[0].len(), rows.len(), Tile::Wall);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                map.set(x, y, Tile::from_symbol(symbol).unwrap());
+            }
+        }
+        map
+    }
+}
+
+impl TileMap for WorldMap {
+    // Las coordenadas negativas quedan fuera
+    fn tile(&self, x: i32, y: i32) -> Option<Tile> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.get(x as usize, y as usize)
+    }
+}